@@ -3,7 +3,12 @@
 //! 包含程序最核心的计算逻辑。
 //! 使用确定性的序贯模型来精确计算钓鱼概率。
 
-use crate::models::{AppConfig, GameData, ProbabilityDetails, ResolvedItem, SpawnFishData};
+use crate::explain::{self, ExplainTrace};
+use crate::models::{
+    AppConfig, Bait, ExcludedEntry, ExclusionReason, FishListResolution, GameData, ParsedFishData, ProbabilityDetails, ResolvedItem,
+    SpawnFishData, SpawnSource,
+};
+use crate::gsq;
 use crate::utils;
 use itertools::Itertools;
 use rayon::prelude::*;
@@ -16,17 +21,63 @@ pub fn get_resolved_fish_list<'a>(
     fish_area_id: &Option<String>,
     is_inherited: bool,
 ) -> Vec<ResolvedItem<'a>> {
+    resolve_fish_list(config, game_data, fish_area_id, is_inherited).included
+}
+
+/// 与 `get_resolved_fish_list` 相同，同时返回被过滤掉的条目及其原因。
+/// 属于其他 FishArea 的条目不计入被过滤的条目。
+pub fn resolve_fish_list<'a>(
+    config: &AppConfig,
+    game_data: &'a GameData,
+    fish_area_id: &Option<String>,
+    is_inherited: bool,
+) -> FishListResolution<'a> {
     let mut call_stack = HashSet::new();
     resolve_location_fish(
         &config.location_name,
-        config,
+        &without_time_of_day(config),
         game_data,
         &mut call_stack,
         fish_area_id,
         is_inherited,
+        None,
     )
 }
 
+/// 与 `get_resolved_fish_list` 相同，同时把与目标物品有关的判断记录到 `trace` 中。
+pub fn resolve_fish_list_traced<'a>(
+    config: &AppConfig,
+    game_data: &'a GameData,
+    fish_area_id: &Option<String>,
+    trace: Option<&mut ExplainTrace>,
+) -> Vec<ResolvedItem<'a>> {
+    let mut call_stack = HashSet::new();
+    resolve_location_fish(&config.location_name, &without_time_of_day(config), game_data, &mut call_stack, fish_area_id, false, trace).included
+}
+
+/// 解析阶段不针对具体时间求值，TIME 等查询一律留给 `filter_items_for_time_segment` 按时间段判断，
+/// 否则用户指定的当前时间会在切分时间段之前就排除掉其他时间段才出现的条目。
+fn without_time_of_day(config: &AppConfig) -> AppConfig {
+    let mut config = config.clone();
+    config.game_state.time_of_day = None;
+    config
+}
+
+/// 列出一个地点需要分析的所有 FishArea。
+/// `None` 代表未指定 FishAreaId 的条目（包括来自 Default 的条目）。
+pub fn get_fish_area_ids(location_name: &str, game_data: &GameData) -> Vec<Option<String>> {
+    let Some(location_data) = game_data.locations.get(location_name) else { return vec![]; };
+
+    let mut fish_area_ids: Vec<Option<String>> = location_data.fish_areas.keys().cloned().map(Some).collect();
+    fish_area_ids.sort();
+    let has_default_area_fish = location_data.fish.iter().any(|f| f.fish_area_id.is_none())
+                             || game_data.locations["Default"].fish.iter().any(|f| f.fish_area_id.is_none());
+    if has_default_area_fish && !fish_area_ids.contains(&None) {
+        fish_area_ids.push(None);
+    }
+    fish_area_ids
+}
+
 /// 递归核心：这个函数现在会执行所有能做的静态过滤，包括 Fish.json 里的天气等。
 fn resolve_location_fish<'a>(
     location_name: &str,
@@ -35,67 +86,172 @@ fn resolve_location_fish<'a>(
     call_stack: &mut HashSet<String>,
     fish_area_id: &Option<String>,
     is_inherited: bool,
-) -> Vec<ResolvedItem<'a>> {
-    if call_stack.contains(location_name) { return vec![]; }
+    mut trace: Option<&mut ExplainTrace>,
+) -> FishListResolution<'a> {
+    let mut resolution = FishListResolution::default();
+    if call_stack.contains(location_name) {
+        explain::note(&mut trace, || format!("{} 已在继承链中，跳过以避免循环", location_name));
+        return resolution;
+    }
     call_stack.insert(location_name.to_string());
 
-    let location_data = match game_data.locations.get(location_name) {
-        Some(data) => data,
-        None => { call_stack.remove(location_name); return vec![]; }
+    let (location_key, location_data) = match game_data.locations.get_key_value(location_name) {
+        Some(entry) => entry,
+        None => { call_stack.remove(location_name); return resolution; }
     };
-    
-    let using_magic_bait = config.bait_item_id.as_deref() == Some("(O)908");
 
-    let possible_fish: Vec<&'a SpawnFishData> = game_data.locations["Default"]
-        .fish.iter()
-        .chain(location_data.fish.iter())
+    // 记录每个条目来自哪个地点以及在该地点 Fish 列表中的下标
+    let (default_key, default_data) = game_data.locations.get_key_value("Default").expect("Locations.json 中缺少 Default");
+    let sourced_fish = |key: &'a String, fish: &'a [SpawnFishData]| {
+        fish.iter().enumerate().map(move |(index, spawn_data)| (SpawnSource { location: key, index }, spawn_data))
+    };
+    let possible_fish: Vec<(SpawnSource<'a>, &'a SpawnFishData)> = sourced_fish(default_key, &default_data.fish)
+        .chain(sourced_fish(location_key, &location_data.fish))
         .collect();
 
-    let mut resolved_items = Vec::new();
-    for spawn_data in possible_fish {
-        let is_from_default = spawn_data as *const _ as usize <= game_data.locations["Default"].fish.last().unwrap() as *const _ as usize;
-        if is_inherited && is_from_default && !spawn_data.can_be_inherited { continue; }
-        if &spawn_data.fish_area_id != fish_area_id { continue; }
-        
-        if config.fishing_level < spawn_data.min_fishing_level { continue; }
-        if config.water_depth < spawn_data.min_distance_from_shore { continue; }
-        if spawn_data.max_distance_from_shore != -1 && config.water_depth > spawn_data.max_distance_from_shore as u32 { continue; }
-        if spawn_data.require_magic_bait && !using_magic_bait { continue; }
-        if let Some(id) = &spawn_data.item_id {
-            if spawn_data.catch_limit == 1 && config.fish_caught.contains_key(id) { continue; }
+    for (source, spawn_data) in possible_fish {
+        // 解释模式只记录与目标物品有关的条目
+        let mut entry_trace = trace.as_deref_mut().filter(|t| t.wants(spawn_data));
+        if let Some(t) = entry_trace.as_deref_mut() { t.entry(source, spawn_data); }
+
+        // 其他 FishArea 的条目不属于当前 FishArea，不记录为被排除
+        if !explain::check(&mut entry_trace, &spawn_data.fish_area_id == fish_area_id, || {
+            format!("FishAreaId：条目 {}，当前 {}", spawn_data.fish_area_id.as_deref().unwrap_or("无"), fish_area_id.as_deref().unwrap_or("无"))
+        }) { continue; }
+
+        let condition_weight = match check_spawn_entry(spawn_data, source, config, game_data, is_inherited, &mut entry_trace) {
+            Ok(weight) => weight,
+            Err(reason) => {
+                resolution.excluded.push(ExcludedEntry { source_data: spawn_data, source, reason });
+                continue;
+            }
+        };
+
+        let entry_resolution = resolve_spawn_entry(spawn_data, source, config, game_data, call_stack, fish_area_id, entry_trace);
+        resolution.included.extend(entry_resolution.included.into_iter().map(|mut item| {
+            item.condition_weight *= condition_weight;
+            item.conditions.extend(spawn_data.condition.as_deref());
+            item
+        }));
+        resolution.excluded.extend(entry_resolution.excluded);
+    }
+
+    call_stack.remove(location_name);
+    resolution
+}
+
+/// 对单个条目执行所有静态过滤。通过时返回 Condition 成立的概率，否则返回第一个未通过的检查。
+fn check_spawn_entry(
+    spawn_data: &SpawnFishData,
+    source: SpawnSource,
+    config: &AppConfig,
+    game_data: &GameData,
+    is_inherited: bool,
+    trace: &mut Option<&mut ExplainTrace>,
+) -> Result<f64, ExclusionReason> {
+    let bait = config.bait();
+    let using_magic_bait = bait == Some(Bait::Magic);
+    let ignores_conditions = bait.is_some_and(Bait::ignores_conditions);
+    let is_from_default = source.location == "Default";
+
+    if !explain::check(trace, !(is_inherited && is_from_default && !spawn_data.can_be_inherited), || {
+        format!("CanBeInherited = {}，{}", spawn_data.can_be_inherited,
+            if is_inherited && is_from_default { "经由 LOCATION_FISH 继承的 Default 条目" } else { "不是继承的 Default 条目" })
+    }) { return Err(ExclusionReason::NotInheritable); }
+    if !explain::check(trace, config.position_allows(spawn_data), || {
+        format!("PlayerPosition {:?} / BobberPosition {:?}，玩家 {:?}，浮标 {:?}",
+            spawn_data.player_position, spawn_data.bobber_position, config.player_tile, config.bobber_tile)
+    }) { return Err(ExclusionReason::PositionRestricted); }
+
+    if !explain::check(trace, config.fishing_level >= spawn_data.min_fishing_level, || {
+        format!("MinFishingLevel {}，钓鱼等级 {}", spawn_data.min_fishing_level, config.fishing_level)
+    }) {
+        return Err(ExclusionReason::FishingLevelTooLow { required: spawn_data.min_fishing_level, current: config.fishing_level });
+    }
+    if !explain::check(trace, config.water_depth >= spawn_data.min_distance_from_shore, || {
+        format!("MinDistanceFromShore {}，水深 {}", spawn_data.min_distance_from_shore, config.water_depth)
+    }) {
+        return Err(ExclusionReason::TooShallow { min_distance: spawn_data.min_distance_from_shore, water_depth: config.water_depth });
+    }
+    if !explain::check(trace, spawn_data.max_distance_from_shore == -1 || config.water_depth <= spawn_data.max_distance_from_shore as u32, || {
+        format!("MaxDistanceFromShore {}（-1 表示不限），水深 {}", spawn_data.max_distance_from_shore, config.water_depth)
+    }) {
+        return Err(ExclusionReason::TooDeep { max_distance: spawn_data.max_distance_from_shore as u32, water_depth: config.water_depth });
+    }
+    if !explain::check(trace, !spawn_data.require_magic_bait || using_magic_bait, || {
+        format!("RequireMagicBait = {}，使用魔法鱼饵 = {}", spawn_data.require_magic_bait, using_magic_bait)
+    }) { return Err(ExclusionReason::RequiresMagicBait); }
+    if let Some(id) = &spawn_data.item_id {
+        let caught = config.fish_caught.contains_key(id);
+        if !explain::check(trace, !(spawn_data.catch_limit == 1 && caught), || {
+            format!("CatchLimit {}，已钓到过 = {}", spawn_data.catch_limit, caught)
+        }) { return Err(ExclusionReason::CatchLimitReached); }
+    }
+
+    let condition_weight = gsq::check_condition(&spawn_data.condition, config);
+    if let (Some(t), Some(condition)) = (trace.as_deref_mut(), &spawn_data.condition) {
+        t.indent();
+        t.line(format!("Condition `{}`", condition));
+        t.indent();
+        for query in gsq::split_queries(condition) {
+            t.line(format!("{} = {:.4}", query, gsq::evaluate_query(&query, config)));
         }
-        if !utils::check_condition(&spawn_data.condition, config) { continue; }
+        t.outdent();
+        t.outdent();
+    }
+    if !explain::check(trace, condition_weight > 0.0, || format!("Condition 成立的概率 {:.4}", condition_weight)) {
+        let failed = spawn_data.condition.as_deref().map_or_else(Vec::new, |condition| {
+            gsq::split_queries(condition).into_iter().filter(|query| gsq::evaluate_query(query, config) <= 0.0).collect()
+        });
+        return Err(ExclusionReason::ConditionFailed { queries: failed });
+    }
 
-        if !using_magic_bait {
-            if let Some(season) = &spawn_data.season {
-                if !season.to_lowercase().split_whitespace().any(|s| s == config.season) { continue; }
+    if ignores_conditions {
+        explain::note(trace, || "鱼饵忽略季节与天气限制".to_string());
+        return Ok(condition_weight);
+    }
+    if let Some(season) = &spawn_data.season {
+        let in_season = season.to_lowercase().split_whitespace().any(|s| s == config.season);
+        if !explain::check(trace, in_season, || format!("Season {}，当前 {}", season, config.season)) {
+            return Err(ExclusionReason::OutOfSeason { seasons: season.to_lowercase(), current: config.season.clone() });
+        }
+    }
+    if !spawn_data.ignore_fish_data_requirements {
+        if let Some(fish_data) = spawn_data.item_id.as_ref().and_then(|id| game_data.fish.get(id)) {
+            let in_season = fish_data.seasons.is_empty() || fish_data.seasons.iter().any(|s| s == &config.season || s == "both");
+            if !explain::check(trace, in_season, || {
+                format!("Fish.json 季节 [{}]，当前 {}", fish_data.seasons.join(" "), config.season)
+            }) {
+                return Err(ExclusionReason::OutOfSeason { seasons: fish_data.seasons.join(" "), current: config.season.clone() });
             }
-            if !spawn_data.ignore_fish_data_requirements {
-                if let Some(id) = &spawn_data.item_id {
-                     if let Some(fish_data) = game_data.fish.get(id) {
-                        if !fish_data.seasons.is_empty() && !fish_data.seasons.iter().any(|s| s == &config.season || s == "both") { continue; }
-                        if fish_data.weather != "both" && fish_data.weather != config.weather { continue; }
-                    }
-                }
+            let in_weather = fish_data.weather == "both" || fish_data.weather == config.weather;
+            if !explain::check(trace, in_weather, || {
+                format!("Fish.json 天气 {}，当前 {}", fish_data.weather, config.weather)
+            }) {
+                return Err(ExclusionReason::WrongWeather { required: fish_data.weather.clone(), current: config.weather.clone() });
             }
         }
-        
-        resolved_items.extend(resolve_spawn_entry(spawn_data, config, game_data, call_stack, fish_area_id));
     }
 
-    call_stack.remove(location_name);
-    resolved_items
+    Ok(condition_weight)
 }
 
 
 /// 解析单个 SpawnFishData 条目，处理特殊指令。
 fn resolve_spawn_entry<'a>(
     spawn_data: &'a SpawnFishData,
+    source: SpawnSource<'a>,
     config: &AppConfig,
     game_data: &'a GameData,
     call_stack: &mut HashSet<String>,
     fish_area_id: &Option<String>,
-) -> Vec<ResolvedItem<'a>> {
+    mut trace: Option<&mut ExplainTrace>,
+) -> FishListResolution<'a> {
+    let single = |display_id: &str| FishListResolution {
+        included: vec![ResolvedItem { display_id: display_id.to_string(), source_data: spawn_data, source, condition_weight: 1.0, conditions: vec![] }],
+        excluded: vec![],
+    };
+
     // --- 核心修正：正确处理 ItemId 和 RandomItemId ---
     
     // 如果有 ItemId，直接使用
@@ -103,48 +259,126 @@ fn resolve_spawn_entry<'a>(
         return match item_id.as_str() {
             "SECRET_NOTE_OR_ITEM" => {
                 let has_all_notes = config.conditions.get("PLAYER_HAS_ALL_SECRET_NOTES") == Some(&"true".to_string());
-                if has_all_notes { vec![] } 
-                else { vec![ResolvedItem { display_id: item_id.to_string(), source_data: spawn_data }] }
+                if has_all_notes {
+                    FishListResolution {
+                        included: vec![],
+                        excluded: vec![ExcludedEntry { source_data: spawn_data, source, reason: ExclusionReason::AllSecretNotesFound }],
+                    }
+                } else { single(item_id) }
             }
             id if id.starts_with("LOCATION_FISH") => {
                 if let Some(target_location) = utils::parse_location_query(id) {
-                    resolve_location_fish(target_location, config, game_data, call_stack, fish_area_id, true)
-                } else { vec![] }
+                    explain::note(&mut trace, || format!("继承 {} 的条目", target_location));
+                    if let Some(t) = trace.as_deref_mut() { t.indent(); t.indent(); }
+                    let resolution = resolve_location_fish(target_location, config, game_data, call_stack, fish_area_id, true, trace.as_deref_mut());
+                    if let Some(t) = trace.as_deref_mut() { t.outdent(); t.outdent(); }
+                    resolution
+                } else { FishListResolution::default() }
+            }
+            _ => {
+                explain::note(&mut trace, || format!("收录 {}", item_id));
+                single(item_id)
             }
-            _ => vec![ResolvedItem { display_id: item_id.to_string(), source_data: spawn_data }],
         };
     }
     
     // 如果没有 ItemId，但有 RandomItemId，则展开它
     if let Some(random_ids) = &spawn_data.random_item_id {
-        return random_ids.iter().map(|id| ResolvedItem {
-            display_id: id.clone(),
-            source_data: spawn_data,
-        }).collect();
+        explain::note(&mut trace, || format!("RandomItemId 展开为 {} 个物品，每个物品分别判定", random_ids.len()));
+        return FishListResolution {
+            included: random_ids.iter().map(|id| ResolvedItem {
+                display_id: id.clone(),
+                source_data: spawn_data,
+                source,
+                condition_weight: 1.0,
+                conditions: vec![],
+            }).collect(),
+            excluded: vec![],
+        };
     }
     
     // 如果两者都没有，返回空
-    vec![]
+    FishListResolution::default()
 }
 
 
 /// 在一个具体的时间段内，对已解析的物品列表进行最终的动态筛选。
+/// 条目的 Condition 会以时间段的开始时间重新求值（例如 TIME、IS_PASSIVE_FESTIVAL_OPEN），
+/// 返回的物品带有该时间段内的条件概率。
 pub fn filter_items_for_time_segment<'a>(
     time_segment: (u32, u32),
-    items: &'a [ResolvedItem<'a>],
+    items: &[ResolvedItem<'a>],
+    config: &AppConfig,
+    game_data: &GameData,
+) -> Vec<ResolvedItem<'a>> {
+    filter_items_for_time_segment_traced(time_segment, items, config, game_data, None)
+}
+
+/// 与 `filter_items_for_time_segment` 相同，同时把目标物品的时间窗口与条件求值记录到 `trace` 中。
+pub(crate) fn filter_items_for_time_segment_traced<'a>(
+    time_segment: (u32, u32),
+    items: &[ResolvedItem<'a>],
     config: &AppConfig,
     game_data: &GameData,
-) -> Vec<&'a ResolvedItem<'a>> {
-    let (start_time, end_time) = time_segment;
-    let using_magic_bait = config.bait_item_id.as_deref() == Some("(O)908");
-
-    items.iter().filter(|item| {
-        if using_magic_bait || item.source_data.ignore_fish_data_requirements { return true; }
-        let Some(fish_data) = game_data.fish.get(&item.display_id) else { return true; };
-        fish_data.time_windows.iter().any(|&(fish_start, fish_end)| start_time < fish_end && end_time > fish_start)
+    mut trace: Option<&mut ExplainTrace>,
+) -> Vec<ResolvedItem<'a>> {
+    // 时间段的边界已包含所有 TIME 查询的边界，段内任意时刻的求值结果相同
+    let segment_config = segment_config(time_segment, config);
+
+    items.iter().filter_map(|item| {
+        let mut item_trace = trace.as_deref_mut().filter(|t| t.is_target(&item.display_id));
+        if let Some(t) = item_trace.as_deref_mut() { t.line(format!("{} 的时间段过滤", item.source)); }
+
+        let condition_weight = check_segment_entry(time_segment, item, &segment_config, game_data, &mut item_trace).ok()?;
+        Some(ResolvedItem { condition_weight, ..item.clone() })
     }).collect()
 }
 
+/// 已解析的物品在该时间段内被过滤掉的原因；没有被过滤时返回 `None`。
+pub fn segment_exclusion(time_segment: (u32, u32), item: &ResolvedItem, config: &AppConfig, game_data: &GameData) -> Option<ExclusionReason> {
+    check_segment_entry(time_segment, item, &segment_config(time_segment, config), game_data, &mut None).err()
+}
+
+/// 以时间段的开始时间求值 Condition 使用的配置。
+fn segment_config((start_time, _): (u32, u32), config: &AppConfig) -> AppConfig {
+    let mut segment_config = config.clone();
+    segment_config.game_state.time_of_day = Some(start_time);
+    segment_config
+}
+
+/// 对单个物品执行时间段内的过滤。通过时返回该时间段内 Condition 成立的概率，否则返回未通过的检查。
+fn check_segment_entry(
+    (start_time, end_time): (u32, u32),
+    item: &ResolvedItem,
+    segment_config: &AppConfig,
+    game_data: &GameData,
+    trace: &mut Option<&mut ExplainTrace>,
+) -> Result<f64, ExclusionReason> {
+    let ignores_conditions = segment_config.bait().is_some_and(Bait::ignores_conditions);
+    if !ignores_conditions && !item.source_data.ignore_fish_data_requirements {
+        if let Some(fish_data) = game_data.fish.get(&item.display_id) {
+            let overlaps = fish_data.time_windows.iter().any(|&(fish_start, fish_end)| start_time < fish_end && end_time > fish_start);
+            if !explain::check(trace, overlaps, || format!("Fish.json 时间窗口 {:?}", fish_data.time_windows)) {
+                return Err(ExclusionReason::OutsideFishTime { windows: fish_data.time_windows.clone() });
+            }
+        }
+    }
+
+    let condition_weight: f64 = item.conditions.iter()
+        .map(|condition| gsq::check_condition_str(condition, segment_config))
+        .product();
+    if !explain::check(trace, condition_weight > 0.0, || {
+        format!("{} 时 Condition 成立的概率 {:.4}", start_time, condition_weight)
+    }) {
+        let failed = item.conditions.iter()
+            .flat_map(|condition| gsq::split_queries(condition))
+            .filter(|query| gsq::evaluate_query(query, segment_config) <= 0.0)
+            .collect();
+        return Err(ExclusionReason::ConditionFailed { queries: failed });
+    }
+    Ok(condition_weight)
+}
+
 /// 顶层函数：统一处理所有鱼饵类型的最终概率计算
 pub fn calculate_final_probabilities<'a>(
     items: &[&'a ResolvedItem<'a>],
@@ -185,7 +419,7 @@ pub fn calculate_final_probabilities<'a>(
     let final_probabilities = if let Some(target_fish_id) = &config.bait_target_fish_id {
         // --- 特制鱼饵的逻辑 ---
         // 特制鱼饵本身就是一种“好鱼饵”，所以固定两次尝试
-        let passes = 2; 
+        let passes = Bait::Targeted.spawn_passes();
         
         // 安全地找到目标鱼的指针
         let target_ptr_opt = items.iter()
@@ -221,7 +455,7 @@ pub fn calculate_final_probabilities<'a>(
 
     } else {
         // --- 标准/好鱼饵的逻辑 ---
-        let passes = if config.using_good_bait { 2 } else { 1 };
+        let passes = config.bait().map_or(1, Bait::spawn_passes);
         let mut final_probs_map = HashMap::new();
         let mut p_uncaught_overall = 1.0;
 
@@ -236,6 +470,8 @@ pub fn calculate_final_probabilities<'a>(
     };
 
     // --- 4. 聚合结果并返回 ---
+    // 野生鱼饵与挑战鱼饵只对有小游戏的鱼增加数量
+    let fish_count = config.bait().map_or(1.0, |bait| bait.expected_fish_count(config.daily_luck, config.perfect_catch_rate));
     let mut results: Vec<ProbabilityDetails> = items.iter().map(|&item| {
         let item_ptr = item as *const ResolvedItem as usize;
         let (get_chance, bite_chance) = get_individual_success_rates(item, config, game_data);
@@ -252,6 +488,11 @@ pub fn calculate_final_probabilities<'a>(
             bite_chance_prob: bite_chance,
             final_prob,
             source_group_id,
+            source: item.source.to_string(),
+            catch_success_prob: None,
+            landed_prob: None,
+            expected_count: if game_data.fish.get(&item.display_id).is_some_and(|fish| !fish.is_algae()) { fish_count } else { 1.0 },
+            quality: None,
         }
     }).collect();
 
@@ -264,120 +505,231 @@ fn calculate_group_probabilities<'a>(
     group: &[&'a ResolvedItem<'a>],
     success_rates: &HashMap<usize, f64>,
 ) -> HashMap<usize, f64> {
-    if group.is_empty() { return HashMap::new(); }
+    let item_ptrs: Vec<usize> = group.iter()
+        .map(|&item| item as *const ResolvedItem as usize)
+        .collect();
+    let rates: Vec<f64> = item_ptrs.iter()
+        .map(|ptr| success_rates.get(ptr).cloned().unwrap_or(0.0))
+        .collect();
 
-    let num_items = group.len();
+    // 同一个物品指针可能在组内出现多次（例如 RandomItemId 中的重复项），需要累加
     let mut total_catch_probs: HashMap<usize, f64> = HashMap::new();
+    for (ptr, prob) in item_ptrs.into_iter().zip(group_first_success_probabilities(&rates)) {
+        *total_catch_probs.entry(ptr).or_insert(0.0) += prob;
+    }
+    total_catch_probs
+}
+
+/// 精确计算“组内随机打乱顺序后逐个判定，第一个成功者被钓到”时，每个物品被钓到的概率。
+///
+/// 对物品 i 而言，排在它前面的其他物品个数 k 在 0..n 上均匀分布，
+/// 且给定 k 时前面的物品集合是一个均匀随机的 k 元子集，因此：
+///
+/// P(i) = p_i * Σ_k e_k(q) * k! (m-k)! / n!
+///
+/// 其中 m = n - 1，q_j = 1 - p_j（j ≠ i），e_k 为初等对称多项式。
+/// 所有项均为非负数，数值稳定，复杂度为 O(n^3)，可以轻松处理 30 个以上的物品。
+pub fn group_first_success_probabilities(rates: &[f64]) -> Vec<f64> {
+    let n = rates.len();
+    if n == 0 { return vec![]; }
+    let m = n - 1;
+
+    // weights[k] = k! (m-k)! / n!，以对数阶乘计算以避免溢出
+    let ln_factorial: Vec<f64> = std::iter::once(0.0)
+        .chain((1..=n).scan(0.0, |acc, i| { *acc += (i as f64).ln(); Some(*acc) }))
+        .collect();
+    let weights: Vec<f64> = (0..=m)
+        .map(|k| (ln_factorial[k] + ln_factorial[m - k] - ln_factorial[n]).exp())
+        .collect();
 
-    if num_items > 8 { // Performance safeguard
-        let total_success_rate: f64 = group.iter().map(|item| {
-            let item_ptr = &**item as *const ResolvedItem as usize;
-            success_rates.get(&item_ptr).cloned().unwrap_or(0.0)
-        }).sum();
-
-        if total_success_rate > 0.0 {
-            let normalization_factor = (1.0 - (1.0 - total_success_rate).max(0.0).min(1.0)) / total_success_rate;
-            for &item in group {
-                let item_ptr = item as *const ResolvedItem as usize;
-                let p_success = success_rates.get(&item_ptr).cloned().unwrap_or(0.0);
-                total_catch_probs.insert(item_ptr, p_success * normalization_factor);
+    (0..n).map(|i| {
+        if rates[i] <= 0.0 { return 0.0; }
+
+        // e[k] = 其他物品中恰好选出 k 个且全部失败的“失败概率乘积之和”
+        let mut e = vec![0.0; n];
+        e[0] = 1.0;
+        let mut count = 0;
+        for (j, &p) in rates.iter().enumerate() {
+            if j == i { continue; }
+            let q = 1.0 - p;
+            count += 1;
+            for k in (1..=count).rev() {
+                e[k] += e[k - 1] * q;
             }
         }
-        return total_catch_probs;
-    }
 
-    for permutation in group.iter().permutations(num_items) {
+        rates[i] * e.iter().zip(&weights).map(|(e_k, w_k)| e_k * w_k).sum::<f64>()
+    }).collect()
+}
+
+/// 通过穷举所有排列计算组内概率，复杂度为 O(n! * n)。
+/// 仅作为 `group_first_success_probabilities` 的参考实现，用于验证小规模组的结果。
+pub fn group_first_success_probabilities_by_permutation(rates: &[f64]) -> Vec<f64> {
+    let num_items = rates.len();
+    let mut total_catch_probs = vec![0.0; num_items];
+    if num_items == 0 { return total_catch_probs; }
+
+    for permutation in (0..num_items).permutations(num_items) {
         let mut p_uncaught_in_perm = 1.0;
-        for &&item in &permutation {
-            let item_ptr = item as *const ResolvedItem as usize;
-            let p_success = success_rates.get(&item_ptr).cloned().unwrap_or(0.0);
-            
-            let p_catch_this = p_uncaught_in_perm * p_success;
-            *total_catch_probs.entry(item_ptr).or_insert(0.0) += p_catch_this;
-            
+        for index in permutation {
+            let p_success = rates[index];
+            total_catch_probs[index] += p_uncaught_in_perm * p_success;
             p_uncaught_in_perm *= 1.0 - p_success;
         }
     }
 
     let num_permutations = (1..=num_items).map(|i| i as f64).product::<f64>();
-
-    if num_permutations > 0.0 {
-        for prob in total_catch_probs.values_mut() { *prob /= num_permutations; }
-    }
+    for prob in total_catch_probs.iter_mut() { *prob /= num_permutations; }
 
     total_catch_probs
 }
 
 /// 计算单个物品的“存活概率”和“咬钩概率”
-fn get_individual_success_rates(item: &ResolvedItem, config: &AppConfig, game_data: &GameData) -> (f64, f64) {
+///
+/// ChanceModifiers 的条件使用传入的 `config` 求值，不会按时间段重新求值，
+/// 其中的 TIME 查询也不参与时间段的切分。目前游戏数据中的 ChanceModifiers 都与时间无关。
+pub(crate) fn get_individual_success_rates(item: &ResolvedItem, config: &AppConfig, game_data: &GameData) -> (f64, f64) {
+    success_rates_traced(item, config, game_data, None)
+}
+
+/// 与 `get_individual_success_rates` 相同，同时把公式中的每一项记录到 `trace` 中。
+pub(crate) fn success_rates_traced(
+    item: &ResolvedItem,
+    config: &AppConfig,
+    game_data: &GameData,
+    mut trace: Option<&mut ExplainTrace>,
+) -> (f64, f64) {
     let is_targeted = config.bait_target_fish_id.as_deref() == Some(&item.display_id);
     
     // --- 存活概率 (GetChance) 计算 ---
     let mut get_chance_prob = item.source_data.chance;
-    if config.has_curiosity_lure && item.source_data.curiosity_lure_buff > 0.0 {
+    explain::note(&mut trace, || format!("GetChance：Chance {:.4}", get_chance_prob));
+    if config.has_curiosity_lure() && item.source_data.curiosity_lure_buff > 0.0 {
         get_chance_prob += item.source_data.curiosity_lure_buff;
+        explain::note(&mut trace, || format!("  + CuriosityLureBuff {:.4} → {:.4}", item.source_data.curiosity_lure_buff, get_chance_prob));
     }
     if item.source_data.apply_daily_luck {
         get_chance_prob += config.daily_luck;
+        explain::note(&mut trace, || format!("  + 每日运气 {:.4} → {:.4}", config.daily_luck, get_chance_prob));
     }
-    if is_targeted {
-        get_chance_prob = get_chance_prob * item.source_data.specific_bait_multiplier + item.source_data.specific_bait_buff;
+    // ChanceModifiers 的条件可能带有随机性，得到的是若干种结果及其概率
+    let chance_outcomes = match &item.source_data.chance_modifiers {
+        Some(modifiers) => utils::apply_quantity_modifiers(get_chance_prob, modifiers, item.source_data.chance_modifier_mode, config),
+        None => vec![(1.0, get_chance_prob)],
+    };
+    let mut weighted_chance = 0.0;
+    for (weight, mut chance) in chance_outcomes {
+        if item.source_data.chance_modifiers.is_some() {
+            explain::note(&mut trace, || format!("  ChanceModifiers：概率 {:.4} 时为 {:.4}", weight, chance));
+        }
+        if is_targeted {
+            chance = chance * item.source_data.specific_bait_multiplier + item.source_data.specific_bait_buff;
+            explain::note(&mut trace, || format!(
+                "  特制鱼饵：× SpecificBaitMultiplier {:.4} + SpecificBaitBuff {:.4} → {:.4}",
+                item.source_data.specific_bait_multiplier, item.source_data.specific_bait_buff, chance
+            ));
+        }
+        if item.source_data.chance_boost_per_luck_level != 0.0 {
+            chance += item.source_data.chance_boost_per_luck_level * config.luck_level as f64;
+            explain::note(&mut trace, || format!(
+                "  + ChanceBoostPerLuckLevel {:.4} × 运气等级 {} → {:.4}",
+                item.source_data.chance_boost_per_luck_level, config.luck_level, chance
+            ));
+        }
+        weighted_chance += weight * chance.clamp(0.0, 1.0);
     }
-    get_chance_prob += item.source_data.chance_boost_per_luck_level * config.luck_level as f64;
+    let get_chance_prob = weighted_chance * item.condition_weight;
+    explain::note(&mut trace, || format!(
+        "  截断到 [0, 1] 并乘以 Condition 概率 {:.4} → GetChance = {:.4}", item.condition_weight, get_chance_prob
+    ));
     
     // --- 咬钩概率 (BiteChance) 计算 ---
     let mut bite_chance_prob = 1.0;
 
     if let Some(fish_data) = game_data.fish.get(&item.display_id) {
-        // 首先，无条件执行训练钓竿的检查
-        if config.is_training_rod {
-            if let Some(false) = item.source_data.can_use_training_rod {
-                bite_chance_prob = 0.0;
-            }
-            else if item.source_data.can_use_training_rod.is_none() && fish_data.difficulty >= 50 {
-                bite_chance_prob = 0.0;
-            }
-        }
-
-        // 只有在训练钓竿检查通过后，才进行后续计算
-        if bite_chance_prob > 0.0 && !item.source_data.ignore_fish_data_requirements {
-            let mut passes = true;
-            if config.is_tutorial_catch && !fish_data.is_tutorial_fish {
-                passes = false;
+        if check_bite_requirements(item, config, fish_data, &mut trace).is_err() {
+            bite_chance_prob = 0.0;
+        } else if item.source_data.ignore_fish_data_requirements {
+            explain::note(&mut trace, || "IgnoreFishDataRequirements：BiteChance = 1".to_string());
+        } else {
+            let mut chance = fish_data.base_chance;
+            let drop_off_amount = fish_data.depth_multiplier * chance;
+            chance -= (fish_data.max_depth as f64 - config.water_depth as f64).max(0.0) * drop_off_amount;
+            explain::note(&mut trace, || format!(
+                "BiteChance：基础概率 {:.4} − DepthMultiplier 衰减 {:.4} × max(最佳水深 {} − 水深 {}, 0) → {:.4}",
+                fish_data.base_chance, drop_off_amount, fish_data.max_depth, config.water_depth, chance
+            ));
+            chance += config.fishing_level as f64 / 50.0;
+            explain::note(&mut trace, || format!("  + 钓鱼等级 {} / 50 → {:.4}", config.fishing_level, chance));
+            
+            chance *= config.rod.bite_chance_multiplier();
+            chance = chance.min(0.9);
+            explain::note(&mut trace, || format!(
+                "  × 钓竿倍率 {:.2}，上限 0.9 → {:.4}", config.rod.bite_chance_multiplier(), chance
+            ));
+            
+            if config.has_curiosity_lure() && chance < 0.25 {
+                if item.source_data.curiosity_lure_buff > -1.0 {
+                    chance += item.source_data.curiosity_lure_buff;
+                } else {
+                    let max = 0.25; let min = 0.08;
+                    chance = (max - min) / max * chance + (max - min) / 2.0;
+                }
+                explain::note(&mut trace, || format!("  珍稀诱钩（概率低于 0.25 时生效）→ {:.4}", chance));
             }
-            if fish_data.min_fishing_level > config.fishing_level {
-                passes = false;
+            
+            if is_targeted {
+                chance *= 1.66;
+                explain::note(&mut trace, || format!("  × 特制鱼饵 1.66 → {:.4}", chance));
             }
-
-            if !passes {
-                bite_chance_prob = 0.0;
-            } else {
-                let mut chance = fish_data.base_chance;
-                let drop_off_amount = fish_data.depth_multiplier * chance;
-                chance -= (fish_data.max_depth as f64 - config.water_depth as f64).max(0.0) * drop_off_amount;
-                chance += config.fishing_level as f64 / 50.0;
-                
-                if config.is_training_rod { chance *= 1.1; }
-                chance = chance.min(0.9);
-                
-                if config.has_curiosity_lure && chance < 0.25 {
-                    if item.source_data.curiosity_lure_buff > -1.0 {
-                        chance += item.source_data.curiosity_lure_buff;
-                    } else {
-                        let max = 0.25; let min = 0.08;
-                        chance = (max - min) / max * chance + (max - min) / 2.0;
-                    }
-                }
-                
-                if is_targeted { chance *= 1.66; }
-                if item.source_data.apply_daily_luck { chance += config.daily_luck; }
-                
-                bite_chance_prob = chance;
+            if item.source_data.apply_daily_luck {
+                chance += config.daily_luck;
+                explain::note(&mut trace, || format!("  + 每日运气 {:.4} → {:.4}", config.daily_luck, chance));
             }
+            
+            bite_chance_prob = chance;
         }
+    } else {
+        explain::note(&mut trace, || "不在 Fish.json 中：BiteChance = 1".to_string());
     }
     
-    (get_chance_prob.clamp(0.0, 1.0), bite_chance_prob.clamp(0.0, 1.0))
+    let bite_chance_prob = bite_chance_prob.clamp(0.0, 1.0);
+    explain::note(&mut trace, || format!("BiteChance = {:.4}，单次判定成功率 {:.4}", bite_chance_prob, get_chance_prob * bite_chance_prob));
+    (get_chance_prob, bite_chance_prob)
+}
+
+/// 已解析的物品因钓竿或 Fish.json 的要求而不会咬钩的原因；会咬钩时返回 `None`。
+pub fn bite_exclusion(item: &ResolvedItem, config: &AppConfig, game_data: &GameData) -> Option<ExclusionReason> {
+    let fish_data = game_data.fish.get(&item.display_id)?;
+    check_bite_requirements(item, config, fish_data, &mut None).err()
+}
+
+/// 咬钩前的检查：训练钓竿能否钓起该鱼，以及 Fish.json 中教程鱼与最低钓鱼等级的要求。
+/// 训练钓竿的检查总会执行，IgnoreFishDataRequirements 只跳过 Fish.json 的要求。
+fn check_bite_requirements(
+    item: &ResolvedItem,
+    config: &AppConfig,
+    fish_data: &ParsedFishData,
+    trace: &mut Option<&mut ExplainTrace>,
+) -> Result<(), ExclusionReason> {
+    if !explain::check(trace, config.rod.can_hook(fish_data.difficulty, item.source_data.can_use_training_rod), || {
+        format!("{} 可以钓起难度 {} 的鱼（CanUseTrainingRod {:?}）", config.rod.name(), fish_data.difficulty, item.source_data.can_use_training_rod)
+    }) {
+        return Err(ExclusionReason::TrainingRodCannotHook { difficulty: fish_data.difficulty });
+    }
+    if item.source_data.ignore_fish_data_requirements { return Ok(()); }
+
+    if !explain::check(trace, !config.is_tutorial_catch || fish_data.is_tutorial_fish, || {
+        format!("教程钓鱼 = {}，教程鱼 = {}", config.is_tutorial_catch, fish_data.is_tutorial_fish)
+    }) {
+        return Err(ExclusionReason::NotTutorialFish);
+    }
+    if !explain::check(trace, fish_data.min_fishing_level <= config.fishing_level, || {
+        format!("Fish.json 最低钓鱼等级 {}，钓鱼等级 {}", fish_data.min_fishing_level, config.fishing_level)
+    }) {
+        return Err(ExclusionReason::FishingLevelTooLow { required: fish_data.min_fishing_level, current: config.fishing_level });
+    }
+    Ok(())
 }
 
 /// 获取物品的最终显示/聚合名称
@@ -386,4 +738,52 @@ pub fn get_resolved_item_name(item: &ResolvedItem, game_data: &GameData) -> Stri
     game_data.fish.get(&item.display_id)
         .map(|data| data.name.clone())
         .unwrap_or_else(|| item.display_id.clone()) // 如果在Fish.json找不到，就直接显示ID
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-12, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn exact_matches_permutation_for_small_groups() {
+        let cases: [&[f64]; 6] = [
+            &[],
+            &[0.5],
+            &[0.3, 0.7],
+            &[0.0, 1.0, 0.25],
+            &[0.12, 0.45, 0.9, 0.33, 0.05],
+            &[0.2, 0.2, 0.2, 0.8, 0.61, 0.07, 0.5, 0.99],
+        ];
+        for rates in cases {
+            assert_close(
+                &group_first_success_probabilities(rates),
+                &group_first_success_probabilities_by_permutation(rates),
+            );
+        }
+    }
+
+    #[test]
+    fn exact_handles_large_groups() {
+        let rates: Vec<f64> = (0..32).map(|i| ((i * 37) % 100) as f64 / 100.0).collect();
+        let probs = group_first_success_probabilities(&rates);
+        let p_none: f64 = rates.iter().map(|p| 1.0 - p).product();
+        let total: f64 = probs.iter().sum();
+        assert!((total + p_none - 1.0).abs() < 1e-9);
+        assert!(probs.iter().all(|p| (0.0..=1.0).contains(p)));
+    }
+
+    #[test]
+    fn identical_rates_split_evenly() {
+        let rates = vec![0.3; 12];
+        let probs = group_first_success_probabilities(&rates);
+        let expected = (1.0 - 0.7f64.powi(12)) / 12.0;
+        assert_close(&probs, &[expected; 12]);
+    }
+}
@@ -0,0 +1,292 @@
+//! src/cli.rs
+//!
+//! 解析命令行参数。
+//! 命令行参数分为两类：文件路径（配置文件、数据目录），
+//! 以及叠加在 config.json (UserConfigRaw) 之上的逐字段覆盖项。
+
+use crate::output::OutputFormat;
+use stardew_fishing_prob::models::ConfigOverrides;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+用法: stardew_fishing_prob [选项]
+
+文件:
+  --config <PATH>          用户配置文件 (默认: ./config.json)
+  --data-dir <DIR>         游戏数据目录，包含 Locations.json / Fish.json / StringMap.json (默认: ./data)
+
+模式与输出:
+  --mode <MODE>            计算模式 (默认: fishing):
+                             fishing   按时间段输出钓鱼概率对比表
+                             day       按时段时长加权的全天汇总
+                             season    所有天气、28 天的全季汇总
+                             search    查询某条鱼的最佳地点、时间与鱼饵/钓具 (需要 --fish)
+                             timing    期望抛竿次数、耗时与每个时间段的钓获数量
+                             money     当前季节与天气下每小时期望收益最高的地点、时间与装备
+                             positions 列出地点中限制玩家或浮标位置的刷鱼条目
+                             simulate  逐次模拟抛竿，验证精确概率是否落在置信区间内
+                             crab-pot  蟹笼每日捕获概率
+  --fish <NAME|ID>         search 模式要查询的鱼，或 timing 模式只显示的鱼，
+                           可用英文名、中文名或 Id，例如 Tuna、(O)130
+  --explain <NAME|ID>      不计算概率表，改为追踪该物品在当前配置下经过的每一个判断：
+                           过滤条件、Condition 子条件、LOCATION_FISH 继承链与成功率公式
+  --top <N>                search、money 模式显示的结果数量 (默认: 20)
+  --casts <N>              simulate 模式每个时间段模拟的抛竿次数 (默认: 100000)
+  --seed <N>               simulate 模式的随机种子 (默认: 1)
+  --minigame               模拟钓鱼小游戏，在 fishing 模式中给出成功钓起的概率
+  --perfect-catch-rate <F> 未启用 --minigame 时假设的完美捕获概率 (0~1，默认: 0)
+  --quality                在 fishing 模式的表格中显示银星/金星/铱星概率与平均尺寸
+  --treasure               在 fishing 模式的表格中显示宝箱概率与战利品的期望数量
+  --unavailable            在 fishing 模式的表格中列出当前配置下钓不到的条目及原因
+                           (JSON 与 CSV 输出总是包含这些条目)
+  --fishing-mastery        已解锁钓鱼精通（宝箱可能成为金色宝箱）
+  --output <FORMAT>        输出格式: table、json 或 csv (默认: table)
+
+配置覆盖 (优先于配置文件中的同名字段):
+  --location <NAME>        地点，例如 Beach、Town
+  --season <SEASON>        季节，例如 夏天
+  --weather <WEATHER>      天气，例如 晴天
+  --rod <ROD>              钓竿类型，例如 铱金钓竿、Advanced Iridium Rod
+  --bait <BAIT>            鱼饵类型，None 表示不使用鱼饵
+  --tackle <TACKLE>        钓具，可重复使用以指定多个 (最多两个)
+  --fishing-level <N>      钓鱼等级
+  --luck-level <N>         运气等级
+  --daily-luck <F>         每日运气
+  --water-depth <N>        水深 (与岸边的距离)
+  --player-tile <X,Y>      玩家所站的图块，用于 PlayerPosition 过滤
+  --bobber-tile <X,Y>      浮标所在的图块，用于 BobberPosition 过滤
+  --profession <NAME>      职业，例如 水手、诱饵大师，可重复使用以指定多个
+  --tutorial-catch         视为教程钓鱼
+
+游戏状态 (用于 Condition 查询):
+  --year <N>               年份
+  --day <N>                当月日期
+  --time <HHMM>            当前时间，例如 1830；指定后只分析包含该时间的时间段
+  --festival-day           当天是节日
+  --bridge-fixed           姜岛北部的桥已修复
+  --mail <ID>              已收到的邮件标记，可重复使用
+  --item <ID>              背包中的物品，可重复使用
+  --special-order-rule <ID> 生效中的特殊订单规则，可重复使用
+  --passive-festival <ID>  正在进行的被动节日，例如 SquidFest，可重复使用
+
+其他:
+  --condition <KEY=VALUE>  设置 conditions 中的一项，可重复使用
+  --fish-caught <ID=N>     记录已钓到的鱼及数量，可重复使用
+
+  -h, --help               显示本帮助";
+
+/// 计算模式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    /// 钓鱼：按 FishArea、时间段、场景输出概率表
+    Fishing,
+    /// 全天汇总
+    Day,
+    /// 全季汇总
+    Season,
+    /// 反向查询：某条鱼的最佳地点、时间与装备
+    Search,
+    /// 期望抛竿次数与耗时
+    Timing,
+    /// 收益排名：每小时期望收益最高的地点、时间与装备
+    Money,
+    /// 限制玩家或浮标位置的刷鱼条目
+    Positions,
+    /// 蒙特卡洛模拟：验证确定性模型的结果
+    Simulate,
+    /// 蟹笼：按 FishArea 输出每日捕获概率
+    CrabPot,
+}
+
+impl std::str::FromStr for RunMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fishing" => Ok(RunMode::Fishing),
+            "day" => Ok(RunMode::Day),
+            "season" => Ok(RunMode::Season),
+            "search" => Ok(RunMode::Search),
+            "timing" => Ok(RunMode::Timing),
+            "money" => Ok(RunMode::Money),
+            "positions" => Ok(RunMode::Positions),
+            "simulate" => Ok(RunMode::Simulate),
+            "crab-pot" => Ok(RunMode::CrabPot),
+            _ => Err(format!("未知的计算模式: {} (可选 fishing、day、season、search、timing、money、positions、simulate、crab-pot)", s)),
+        }
+    }
+}
+
+/// 命令行解析后得到的运行参数。
+#[derive(Debug)]
+pub struct CliArgs {
+    pub config_path: PathBuf,
+    pub data_dir: PathBuf,
+    pub mode: RunMode,
+    pub output: OutputFormat,
+    /// 表格中是否显示品质分布
+    pub show_quality: bool,
+    /// 表格中是否显示宝箱概率与战利品
+    pub show_treasure: bool,
+    /// 表格中是否列出被过滤掉的条目
+    pub show_unavailable: bool,
+    /// search 模式查询的鱼，或 timing 模式只显示的鱼
+    pub fish: Option<String>,
+    /// 要追踪的物品，指定后忽略 --mode
+    pub explain: Option<String>,
+    /// search、money 模式显示的结果数量
+    pub top: usize,
+    /// simulate 模式每个时间段模拟的抛竿次数
+    pub casts: u64,
+    /// simulate 模式的随机种子
+    pub seed: u64,
+    pub overrides: ConfigOverrides,
+}
+
+/// 命令行解析的结果：正常运行，或仅显示帮助。
+#[derive(Debug)]
+pub enum CliCommand {
+    Run(Box<CliArgs>),
+    Help,
+}
+
+/// 从 `std::env::args()` 解析命令行。
+pub fn parse_env_args() -> Result<CliCommand, String> {
+    parse_args(std::env::args().skip(1))
+}
+
+/// 解析命令行参数（不包含程序名）。支持 `--flag value` 与 `--flag=value` 两种写法。
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<CliCommand, String> {
+    let mut cli_args = CliArgs {
+        config_path: PathBuf::from("config.json"),
+        data_dir: PathBuf::from("data"),
+        mode: RunMode::Fishing,
+        output: OutputFormat::Table,
+        show_quality: false,
+        show_treasure: false,
+        show_unavailable: false,
+        fish: None,
+        explain: None,
+        top: 20,
+        casts: 100_000,
+        seed: 1,
+        overrides: ConfigOverrides::default(),
+    };
+    let overrides = &mut cli_args.overrides;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+
+        // 无参数的开关
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "--tutorial-catch" => {
+                overrides.is_tutorial_catch = Some(true);
+                continue;
+            }
+            "--festival-day" => {
+                overrides.is_festival_day = Some(true);
+                continue;
+            }
+            "--quality" => {
+                cli_args.show_quality = true;
+                continue;
+            }
+            "--treasure" => {
+                cli_args.show_treasure = true;
+                continue;
+            }
+            "--unavailable" => {
+                cli_args.show_unavailable = true;
+                continue;
+            }
+            "--fishing-mastery" => {
+                overrides.has_fishing_mastery = Some(true);
+                continue;
+            }
+            "--minigame" => {
+                overrides.minigame_enabled = Some(true);
+                continue;
+            }
+            "--bridge-fixed" => {
+                overrides.island_north_bridge_fixed = Some(true);
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut value = || {
+            inline_value.clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("参数 {} 缺少取值", flag))
+        };
+
+        match flag.as_str() {
+            "--config" => cli_args.config_path = PathBuf::from(value()?),
+            "--data-dir" => cli_args.data_dir = PathBuf::from(value()?),
+            "--mode" => cli_args.mode = value()?.parse()?,
+            "--output" => cli_args.output = value()?.parse()?,
+            "--fish" => cli_args.fish = Some(value()?),
+            "--explain" => cli_args.explain = Some(value()?),
+            "--top" => cli_args.top = parse_number(&flag, &value()?)?,
+            "--casts" => cli_args.casts = parse_number(&flag, &value()?)?,
+            "--seed" => cli_args.seed = parse_number(&flag, &value()?)?,
+            "--location" => overrides.location_name = Some(value()?),
+            "--season" => overrides.season = Some(value()?),
+            "--weather" => overrides.weather = Some(value()?),
+            "--rod" => overrides.rod_type = Some(value()?),
+            "--bait" => overrides.bait_type = Some(value()?),
+            "--tackle" => overrides.tackles.get_or_insert_with(Vec::new).push(value()?),
+            "--perfect-catch-rate" => overrides.perfect_catch_rate = Some(parse_number(&flag, &value()?)?),
+            "--fishing-level" => overrides.fishing_level = Some(parse_number(&flag, &value()?)?),
+            "--luck-level" => overrides.luck_level = Some(parse_number(&flag, &value()?)?),
+            "--daily-luck" => overrides.daily_luck = Some(parse_number(&flag, &value()?)?),
+            "--water-depth" => overrides.water_depth = Some(parse_number(&flag, &value()?)?),
+            "--player-tile" => overrides.player_tile = Some(parse_tile(&flag, &value()?)?),
+            "--bobber-tile" => overrides.bobber_tile = Some(parse_tile(&flag, &value()?)?),
+            "--profession" => overrides.professions.get_or_insert_with(Vec::new).push(value()?),
+            "--year" => overrides.year = Some(parse_number(&flag, &value()?)?),
+            "--day" => overrides.day_of_month = Some(parse_number(&flag, &value()?)?),
+            "--time" => overrides.time_of_day = Some(parse_number(&flag, &value()?)?),
+            "--mail" => overrides.mail_received.push(value()?),
+            "--item" => overrides.items.push(value()?),
+            "--special-order-rule" => overrides.special_order_rules.push(value()?),
+            "--passive-festival" => overrides.passive_festivals.push(value()?),
+            "--condition" => {
+                let (key, val) = split_key_value(&flag, &value()?)?;
+                overrides.conditions.push((key, val));
+            }
+            "--fish-caught" => {
+                let (id, count) = split_key_value(&flag, &value()?)?;
+                overrides.fish_caught.push((id, parse_number(&flag, &count)?));
+            }
+            _ => return Err(format!("未知参数: {}", flag)),
+        }
+    }
+
+    if cli_args.mode == RunMode::Search && cli_args.fish.is_none() && cli_args.explain.is_none() {
+        return Err("search 模式需要通过 --fish 指定要查询的鱼".to_string());
+    }
+
+    Ok(CliCommand::Run(Box::new(cli_args)))
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("参数 {} 的取值无效: {}", flag, value))
+}
+
+fn parse_tile(flag: &str, value: &str) -> Result<(i32, i32), String> {
+    let (x, y) = value.split_once(',')
+        .ok_or_else(|| format!("参数 {} 需要 X,Y 格式: {}", flag, value))?;
+    Ok((parse_number(flag, x.trim())?, parse_number(flag, y.trim())?))
+}
+
+fn split_key_value(flag: &str, value: &str) -> Result<(String, String), String> {
+    value.split_once('=')
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .ok_or_else(|| format!("参数 {} 需要 KEY=VALUE 格式: {}", flag, value))
+}
@@ -5,51 +5,135 @@
 //! 结合游戏数据 (GameData)，转换为程序内部使用的、
 //! 经过精确处理的配置 (AppConfig)。
 
+use crate::error::{ConfigError, Error};
 use crate::models::{
-    AppConfig, GameData, LocationData, ParsedFishData, StringMap, UserConfigRaw,
+    AppConfig, Bait, ConfigOverrides, GameData, LocationData, MotionType, ObjectData, ParsedCrabPotData, ParsedFishData, Rod, StringMap, Tackle,
+    TreasureLootData, UserConfigRaw,
 };
-use std::collections::HashMap;
+use crate::treasure;
+use serde::de::{DeserializeOwned, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-
-pub fn load_and_build_config() -> Result<(AppConfig, GameData), Box<dyn std::error::Error>> {
-    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-
-    let config_path = manifest_dir.join("config.json");
-    let locations_path = manifest_dir.join("data/Locations.json");
-    let fish_path = manifest_dir.join("data/Fish.json");
-    let string_map_path = manifest_dir.join("data/StringMap.json");
-
-    let raw_config: UserConfigRaw = serde_json::from_str(&fs::read_to_string(config_path)?)?;
-    let locations: HashMap<String, LocationData> = serde_json::from_str(&fs::read_to_string(locations_path)?)?;
-    let raw_fish_data: HashMap<String, String> = serde_json::from_str(&fs::read_to_string(fish_path)?)?;
-    let string_map: StringMap = serde_json::from_str(&fs::read_to_string(string_map_path)?)?;
-    
-    let (fish, fish_name_to_id) = parse_fish_data(raw_fish_data)?;
-    
-    let game_data = GameData {
+use std::path::Path;
+
+/// 从指定的配置文件和数据目录加载所有数据，并在用户配置之上应用命令行覆盖项。
+pub fn load_and_build_config(
+    config_path: &Path,
+    data_dir: &Path,
+    overrides: &ConfigOverrides,
+) -> Result<(AppConfig, GameData), Error> {
+    let mut raw_config = load_user_config(config_path)?;
+    overrides.apply_to(&mut raw_config);
+
+    let game_data = load_game_data(data_dir)?;
+    let app_config = build_app_config(&raw_config, &game_data)?;
+
+    Ok((app_config, game_data))
+}
+
+/// 读取用户配置文件 (config.json)。
+pub fn load_user_config(config_path: &Path) -> Result<UserConfigRaw, Error> {
+    read_json(config_path)
+}
+
+/// 从数据目录加载 Locations.json、Fish.json 与 StringMap.json。
+/// 可选的 TreasureLoot.json 中继续抽取的概率不在 [0, 1) 之间时返回错误。
+pub fn load_game_data(data_dir: &Path) -> Result<GameData, Error> {
+    let locations: HashMap<String, LocationData> = read_json(&data_dir.join("Locations.json"))?;
+    let raw_fish_data: OrderedEntries = read_json(&data_dir.join("Fish.json"))?;
+    let string_map: StringMap = read_json(&data_dir.join("StringMap.json"))?;
+
+    let (fish, fish_name_to_id, crab_pot_fish) = parse_fish_data(raw_fish_data.0);
+
+    // Objects.json 是可选的，只用于售价相关的计算与蟹笼垃圾的名称
+    let objects_path = data_dir.join("Objects.json");
+    let objects: HashMap<String, ObjectData> = if objects_path.exists() {
+        read_json::<HashMap<String, ObjectData>>(&objects_path)?
+            .into_iter()
+            .map(|(id, data)| (format!("(O){}", id), data))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    // TreasureLoot.json 同样是可选的，只用于宝箱战利品的估算
+    let treasure_loot_path = data_dir.join("TreasureLoot.json");
+    let treasure_loot: Option<TreasureLootData> =
+        if treasure_loot_path.exists() { Some(read_json(&treasure_loot_path)?) } else { None };
+    if let Some(loot) = &treasure_loot {
+        treasure::validate_loot(loot)?;
+    }
+
+    Ok(GameData {
         locations,
         fish,
         fish_name_to_id,
-    };
+        crab_pot_fish,
+        objects,
+        treasure_loot,
+        string_map,
+    })
+}
 
-    let app_config = build_app_config(&raw_config, &game_data, &string_map)
-        .map_err(|e| Box::<dyn std::error::Error>::from(e))?;
+/// 按文件中的原始顺序保存 JSON 对象的键值对。
+/// 游戏按 Fish.json 的顺序依次判定蟹笼物品，因此不能使用 HashMap。
+struct OrderedEntries(Vec<(String, String)>);
 
-    Ok((app_config, game_data))
+impl<'de> Deserialize<'de> for OrderedEntries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = OrderedEntries;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a map of string to string")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(OrderedEntries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let text = fs::read_to_string(path)
+        .map_err(|source| Error::Io { path: path.to_path_buf(), source })?;
+    serde_json::from_str(&text)
+        .map_err(|source| Error::Json { path: path.to_path_buf(), source })
 }
 
+/// parse_fish_data 的返回值：(按物品ID索引的鱼数据, 英文名到物品ID的映射, 按文件顺序排列的蟹笼物品)。
+type ParsedFishTables = (
+    HashMap<String, ParsedFishData>,
+    HashMap<String, String>,
+    Vec<ParsedCrabPotData>,
+);
+
 /// 解析 Fish.json 的原始字符串数据，将其转换为结构化的 ParsedFishData。
-fn parse_fish_data(
-    raw_data: HashMap<String, String>,
-) -> Result<(HashMap<String, ParsedFishData>, HashMap<String, String>), String> {
+/// trap 类型的条目单独解析为 ParsedCrabPotData，并保持文件中的顺序。
+fn parse_fish_data(raw_data: Vec<(String, String)>) -> ParsedFishTables {
     let mut fish = HashMap::new();
     let mut fish_name_to_id = HashMap::new();
+    let mut crab_pot_fish = Vec::new();
 
     for (id, value) in raw_data {
         let parts: Vec<&str> = value.split('/').collect();
-        // 增加对 trap 鱼的过滤
-        if parts.get(1) == Some(&"trap") || parts.len() < 13 { continue; }
+        if parts.get(1) == Some(&"trap") {
+            if let Some(parsed) = parse_crab_pot_data(&id, &parts) {
+                crab_pot_fish.push(parsed);
+            }
+            continue;
+        }
+        if parts.len() < 13 { continue; }
 
         let name = parts[0].to_string();
 
@@ -69,6 +153,9 @@ fn parse_fish_data(
         let parsed = ParsedFishData {
             name: name.clone(),
             difficulty: parts[1].parse().unwrap_or(0),
+            motion_type: MotionType::parse(parts[2]),
+            min_size: parts[3].parse().unwrap_or(1),
+            max_size: parts[4].parse().unwrap_or(1),
             time_windows,
             seasons,
             weather: parts[7].to_string(),
@@ -76,7 +163,7 @@ fn parse_fish_data(
             min_fishing_level: parts[12].parse().unwrap_or(0),
             base_chance: parts[10].parse().unwrap_or(0.0),
             depth_multiplier: parts[11].parse().unwrap_or(0.0),
-            is_tutorial_fish: parts.get(13).map_or(false, |&s| s.parse().unwrap_or(false)),
+            is_tutorial_fish: parts.get(13).is_some_and(|&s| s.parse().unwrap_or(false)),
         };
 
         let item_id = format!("(O){}", id);
@@ -84,24 +171,40 @@ fn parse_fish_data(
         fish_name_to_id.insert(name, item_id);
     }
 
-    Ok((fish, fish_name_to_id))
+    (fish, fish_name_to_id, crab_pot_fish)
+}
+
+/// 解析 trap 条目，格式为 `名称/trap/概率/鱼饵偏好/水域类型/最小尺寸/最大尺寸/...`。
+fn parse_crab_pot_data(id: &str, parts: &[&str]) -> Option<ParsedCrabPotData> {
+    if parts.len() < 5 { return None; }
+    Some(ParsedCrabPotData {
+        item_id: format!("(O){}", id),
+        name: parts[0].to_string(),
+        chance: parts[2].parse().unwrap_or(0.0),
+        water_types: parts[4].split_whitespace().map(|s| s.to_string()).collect(),
+    })
 }
 
 
-fn build_app_config(
+/// 将人类可读的用户配置转换为供计算使用的 AppConfig。
+pub fn build_app_config(
     raw_config: &UserConfigRaw,
     game_data: &GameData,
-    string_map: &StringMap,
-) -> Result<AppConfig, String> {
-    let is_training_rod = string_map.rod_internal_ids.get(&raw_config.rod_type).is_some();
+) -> Result<AppConfig, ConfigError> {
+    let string_map = &game_data.string_map;
+    let rod = string_map.rod_internal_ids.get(&raw_config.rod_type)
+        .and_then(|item_id| Rod::from_item_id(item_id))
+        .ok_or_else(|| ConfigError::UnknownRod(raw_config.rod_type.clone()))?;
 
-    let (bait_item_id, bait_target_fish_id, using_good_bait) =
+    let (bait_item_id, bait_target_fish_id) =
         match raw_config.bait_type.as_str() {
-            "None" | "" => (None, None, false),
+            "None" | "" => (None, None),
             bait_name if string_map.bait_internal_ids.contains_key(bait_name) => {
                 let item_id = string_map.bait_internal_ids[bait_name].clone();
-                let good_bait = item_id != "(O)685";
-                (Some(item_id), None, good_bait)
+                if Bait::from_item_id(&item_id).is_none() {
+                    return Err(ConfigError::UnknownBait(item_id));
+                }
+                (Some(item_id), None)
             }
             specific_bait_name => {
                 let mut target_id = None;
@@ -117,28 +220,62 @@ fn build_app_config(
                     }
                 }
                 if let Some(id) = target_id {
-                    (Some("(O)SpecificBait".to_string()), Some(id), true)
+                    (Some(Bait::Targeted.item_id().to_string()), Some(id))
                 } else {
-                    return Err(format!("无法识别的特制鱼饵: {}", specific_bait_name));
+                    return Err(ConfigError::UnknownSpecificBait(specific_bait_name.to_string()));
                 }
             }
         };
 
-    let has_curiosity_lure = raw_config.tackles.iter()
-        .any(|tackle_name| string_map.tackle_internal_ids.get(tackle_name).is_some());
+    // 空字符串表示空的钓具槽位
+    let tackles = raw_config.tackles.iter()
+        .filter(|tackle_name| !tackle_name.is_empty())
+        .map(|tackle_name| string_map.tackle_internal_ids.get(tackle_name)
+            .and_then(|item_id| Tackle::from_item_id(item_id))
+            .ok_or_else(|| ConfigError::UnknownTackle(tackle_name.clone())))
+        .collect::<Result<Vec<Tackle>, ConfigError>>()?;
+
+    // 钓竿必须能装下配置的鱼饵与钓具，水深也不能超过抛竿能达到的距离
+    if bait_item_id.is_some() && rod.bait_slots() == 0 {
+        return Err(ConfigError::RodCannotHoldBait(rod.name().to_string()));
+    }
+    if tackles.len() > rod.tackle_slots() {
+        return Err(ConfigError::TooManyTackles { rod: rod.name().to_string(), count: tackles.len(), slots: rod.tackle_slots() });
+    }
+    let max_water_depth = max_water_depth(raw_config.fishing_level);
+    if raw_config.water_depth > max_water_depth {
+        return Err(ConfigError::WaterDepthOutOfReach { depth: raw_config.water_depth, max: max_water_depth });
+    }
+
+    if !game_data.locations.contains_key(&raw_config.location_name) {
+        return Err(ConfigError::UnknownLocation(raw_config.location_name.clone()));
+    }
+
+    let professions = raw_config.professions.iter()
+        .map(|name| string_map.professions.get(name).cloned()
+            .ok_or_else(|| ConfigError::UnknownProfession(name.clone())))
+        .collect::<Result<HashSet<String>, ConfigError>>()?;
+
+    // 兼容旧版配置：将 conditions 中已有结构化字段的键迁移到 game_state
+    let mut game_state = raw_config.game_state.clone();
+    if raw_config.conditions.get("IS_FESTIVAL_DAY").is_some_and(|v| v == "true") {
+        game_state.is_festival_day = true;
+    }
+    if let Some(rule) = raw_config.conditions.get("PLAYER_SPECIAL_ORDER_RULE_ACTIVE Current") {
+        if !rule.is_empty() { game_state.special_order_rules.insert(rule.clone()); }
+    }
 
     let season = string_map.seasons.get(&raw_config.season)
-        .ok_or_else(|| format!("Invalid season: {}", raw_config.season))?.clone();
+        .ok_or_else(|| ConfigError::InvalidSeason(raw_config.season.clone()))?.clone();
     let weather = string_map.weather.get(&raw_config.weather)
-        .ok_or_else(|| format!("Invalid weather: {}", raw_config.weather))?.clone();
+        .ok_or_else(|| ConfigError::InvalidWeather(raw_config.weather.clone()))?.clone();
         
     Ok(AppConfig {
         is_tutorial_catch: raw_config.is_tutorial_catch,
-        is_training_rod,
-        using_good_bait,
+        rod,
         bait_item_id,
         bait_target_fish_id,
-        has_curiosity_lure,
+        tackles,
         location_name: raw_config.location_name.clone(),
         season,
         weather,
@@ -148,5 +285,29 @@ fn build_app_config(
         daily_luck: raw_config.daily_luck,
         conditions: raw_config.conditions.clone(),
         fish_caught: raw_config.fish_caught.clone().into_iter().collect(),
+        professions,
+        game_state,
+        cast_timing: raw_config.cast_timing.clone(),
+        minigame: raw_config.minigame.clone(),
+        perfect_catch_rate: raw_config.perfect_catch_rate.clamp(0.0, 1.0),
+        has_fishing_mastery: raw_config.has_fishing_mastery,
+        player_tile: raw_config.player_tile,
+        bobber_tile: raw_config.bobber_tile,
     })
-}
\ No newline at end of file
+}
+/// 蓄满力时的最远抛竿距离（格）。与游戏一致，所有钓竿相同，只随钓鱼等级增加。
+fn max_cast_tiles(fishing_level: u32) -> u32 {
+    let added_distance = match fishing_level {
+        15.. => 4,
+        8..=14 => 3,
+        4..=7 => 2,
+        1..=3 => 1,
+        0 => 0,
+    };
+    4 + added_distance
+}
+
+/// 能达到的最大水深：浮标到岸边的距离不会超过抛竿距离，游戏中水深最大为 5。
+fn max_water_depth(fishing_level: u32) -> u32 {
+    max_cast_tiles(fishing_level).min(5)
+}
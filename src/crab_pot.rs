@@ -0,0 +1,126 @@
+//! src/crab_pot.rs
+//!
+//! 蟹笼的捕获概率计算。
+//! 对应游戏中 CrabPot.DayUpdate 的逻辑：
+//! 1. 先以 FishArea 的 CrabPotJunkChance 判定是否直接得到垃圾（水手职业为 0）；
+//! 2. 否则按 Fish.json 的顺序，依次对水域类型匹配的 trap 物品进行概率判定，第一个成功者被捕获；
+//! 3. 全部失败则得到垃圾。
+//!
+//! 水手职业不进行逐个判定，而是在匹配的物品中等概率选择一个。
+
+use crate::models::{AppConfig, GameData};
+use serde::Serialize;
+
+/// 蟹笼未设置 FishArea 数据时使用的默认值，与游戏一致。
+const DEFAULT_CRAB_POT_JUNK_CHANCE: f64 = 0.2;
+const DEFAULT_CRAB_POT_FISH_TYPE: &str = "freshwater";
+
+/// 蟹笼垃圾：垃圾、浮木、破眼镜、破CD、湿报纸，等概率出现。
+pub const CRAB_POT_JUNK_IDS: [&str; 5] = ["(O)168", "(O)169", "(O)170", "(O)171", "(O)172"];
+
+/// 单个蟹笼物品的每日捕获概率。
+#[derive(Debug, Clone, Serialize)]
+pub struct CrabPotProbability {
+    pub display_id: String,
+    pub name: String,
+    pub is_junk: bool,
+    pub probability: f64,
+}
+
+/// 一个地点、一个 FishArea 的蟹笼结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct CrabPotReport {
+    pub location: String,
+    pub fish_area: Option<String>,
+    pub fish_types: Vec<String>,
+    pub junk_chance: f64,
+    /// 没有鱼饵且没有诱饵大师职业时，蟹笼不会捕获任何东西
+    pub is_active: bool,
+    pub items: Vec<CrabPotProbability>,
+}
+
+/// 为配置中地点的每个 FishArea 计算蟹笼结果。
+pub fn build_crab_pot_report(config: &AppConfig, game_data: &GameData) -> Vec<CrabPotReport> {
+    let Some(location_data) = game_data.locations.get(&config.location_name) else { return vec![]; };
+
+    let mut fish_area_ids: Vec<Option<String>> = location_data.fish_areas.keys().cloned().map(Some).collect();
+    fish_area_ids.sort();
+    if fish_area_ids.is_empty() { fish_area_ids.push(None); }
+
+    fish_area_ids.into_iter()
+        .map(|area_id| calculate_crab_pot_probabilities(config, game_data, area_id))
+        .collect()
+}
+
+/// 计算一个 FishArea 中蟹笼每天捕获各物品的概率。
+pub fn calculate_crab_pot_probabilities(
+    config: &AppConfig,
+    game_data: &GameData,
+    fish_area_id: Option<String>,
+) -> CrabPotReport {
+    let area_data = fish_area_id.as_ref().and_then(|id| {
+        game_data.locations.get(&config.location_name)?.fish_areas.get(id)
+    });
+
+    let fish_types: Vec<String> = match area_data {
+        Some(data) if !data.crab_pot_fish_types.is_empty() => data.crab_pot_fish_types.clone(),
+        _ => vec![DEFAULT_CRAB_POT_FISH_TYPE.to_string()],
+    };
+    let is_mariner = config.professions.contains("Mariner");
+    let is_luremaster = config.professions.contains("Luremaster");
+    let junk_chance = if is_mariner { 0.0 } else { area_data.map_or(DEFAULT_CRAB_POT_JUNK_CHANCE, |d| d.crab_pot_junk_chance) };
+    let is_active = config.bait_item_id.is_some() || is_luremaster;
+
+    let mut report = CrabPotReport {
+        location: config.location_name.clone(),
+        fish_area: fish_area_id,
+        fish_types,
+        junk_chance,
+        is_active,
+        items: Vec::new(),
+    };
+    if !is_active { return report; }
+
+    let candidates: Vec<_> = game_data.crab_pot_fish.iter()
+        .filter(|fish| fish.water_types.iter().any(|t| report.fish_types.contains(t)))
+        .collect();
+
+    let mut p_junk = junk_chance;
+    if is_mariner && !candidates.is_empty() {
+        let p_each = (1.0 - junk_chance) / candidates.len() as f64;
+        for fish in &candidates {
+            report.items.push(CrabPotProbability {
+                display_id: fish.item_id.clone(),
+                name: fish.name.clone(),
+                is_junk: false,
+                probability: p_each,
+            });
+        }
+    } else {
+        let mut p_unresolved = 1.0 - junk_chance;
+        for fish in &candidates {
+            let chance = fish.chance.clamp(0.0, 1.0);
+            report.items.push(CrabPotProbability {
+                display_id: fish.item_id.clone(),
+                name: fish.name.clone(),
+                is_junk: false,
+                probability: p_unresolved * chance,
+            });
+            p_unresolved *= 1.0 - chance;
+        }
+        p_junk += p_unresolved;
+    }
+
+    for junk_id in CRAB_POT_JUNK_IDS {
+        // 垃圾不在 Fish.json 中，名称取自 Objects.json，没有该文件时直接显示物品ID
+        let name = game_data.objects.get(junk_id).map_or(junk_id, |object| object.name.as_str());
+        report.items.push(CrabPotProbability {
+            display_id: junk_id.to_string(),
+            name: name.to_string(),
+            is_junk: true,
+            probability: p_junk / CRAB_POT_JUNK_IDS.len() as f64,
+        });
+    }
+
+    report
+}
@@ -0,0 +1,252 @@
+//! src/economy.rs
+//!
+//! 收益分析：把每次抛竿的概率换算为期望售价，并按每小时（游戏内）收益排名。
+//!
+//! 售价取自 Objects.json 的 Price 字段，按品质乘以 1 / 1.25 / 1.5 / 2（普通/银星/金星/铱星）并向下取整；
+//! 鱼类（Category 为 -4）再乘以职业加成：垂钓者 1.5，否则渔夫 1.25（两者不叠加）。
+//! 启用小游戏模拟时按 landed_prob 计算，否则按 final_prob 计算（假设每次咬钩都能钓起），
+//! 再乘以鱼饵带来的期望数量 (expected_count)。
+
+use crate::calculator;
+use crate::minigame::MinigameEstimator;
+use crate::models::{AppConfig, FishQuality, GameData, ProbabilityDetails, ResolvedItem};
+use crate::quality;
+use crate::search;
+use crate::timing;
+use crate::utils;
+use serde::Serialize;
+
+/// 普通、银星、金星、铱星的售价倍率。
+pub const QUALITY_PRICE_MULTIPLIERS: [f64; 4] = [1.0, 1.25, 1.5, 2.0];
+
+/// 一个地点、时间段与装备组合的收益。
+#[derive(Debug, Clone, Serialize)]
+pub struct MoneySpot {
+    pub location: String,
+    pub fish_area: Option<String>,
+    pub start_time: u32,
+    pub end_time: u32,
+    pub setup: String,
+    /// 每次抛竿的期望售价
+    pub value_per_cast: f64,
+    /// 每游戏小时的期望抛竿次数
+    pub casts_per_hour: f64,
+    /// 每游戏小时的期望收益
+    pub value_per_hour: f64,
+    /// 没有售价数据的物品所占的概率，这部分按 0 计入收益
+    pub unpriced_prob: f64,
+}
+
+/// 物品在职业加成下的售价倍率。
+pub fn profession_multiplier(is_fish: bool, config: &AppConfig) -> f64 {
+    if !is_fish { return 1.0; }
+    if config.professions.contains("Angler") {
+        1.5
+    } else if config.professions.contains("Fisher") {
+        1.25
+    } else {
+        1.0
+    }
+}
+
+/// 物品的期望售价。Objects.json 中没有该物品时返回 `None`。
+pub fn expected_sell_price(
+    display_id: &str,
+    quality: Option<&FishQuality>,
+    config: &AppConfig,
+    game_data: &GameData,
+) -> Option<f64> {
+    let object = game_data.objects.get(display_id)?;
+    let profession = profession_multiplier(object.is_fish(), config);
+    let price = |quality_multiplier: f64| ((object.price as f64 * quality_multiplier).floor() * profession).floor();
+
+    Some(match quality {
+        Some(q) => [q.normal, q.silver, q.gold, q.iridium].iter()
+            .zip(QUALITY_PRICE_MULTIPLIERS)
+            .map(|(prob, multiplier)| prob * price(multiplier))
+            .sum(),
+        None => price(1.0),
+    })
+}
+
+/// 一个时间段中每次抛竿的期望售价，以及没有售价数据的物品所占的概率。
+pub fn value_per_cast(probabilities: &[ProbabilityDetails], config: &AppConfig, game_data: &GameData) -> (f64, f64) {
+    let mut value = 0.0;
+    let mut unpriced_prob = 0.0;
+    for p in probabilities {
+        let prob = p.landed_prob.unwrap_or(p.final_prob) * p.expected_count;
+        match expected_sell_price(&p.display_id, p.quality.as_ref(), config, game_data) {
+            Some(price) => value += prob * price,
+            None => unpriced_prob += prob,
+        }
+    }
+    (value, unpriced_prob)
+}
+
+/// 每游戏小时的期望抛竿次数：一小时的现实秒数除以每次抛竿的期望耗时。
+fn casts_per_hour(probabilities: &[ProbabilityDetails], config: &AppConfig, game_data: &GameData) -> f64 {
+    60.0 * config.cast_timing.seconds_per_game_minute / timing::expected_seconds_per_cast(probabilities, config, game_data)
+}
+
+/// 在当前季节与天气下，遍历所有地点、FishArea、时间段与装备组合，按每小时收益从高到低排列。
+/// 配置中指定了当前时间 (game_state.time_of_day) 时，只保留包含该时间的时间段。
+pub fn rank_money_spots(config: &AppConfig, game_data: &GameData) -> Vec<MoneySpot> {
+    let mut location_names: Vec<&String> = game_data.locations.keys()
+        .filter(|name| name.as_str() != "Default")
+        .collect();
+    location_names.sort();
+
+    let setups = search::build_setups(None, config, game_data);
+    let mut minigame = MinigameEstimator::new();
+    let mut spots = Vec::new();
+
+    for location_name in location_names {
+        for area_id in calculator::get_fish_area_ids(location_name, game_data) {
+            for (setup_name, setup) in &setups {
+                let mut setup_config = setup.clone();
+                setup_config.location_name = location_name.clone();
+
+                let base_items = calculator::get_resolved_fish_list(&setup_config, game_data, &area_id, false);
+                for segment in utils::calculate_time_segments(&base_items, game_data) {
+                    if !utils::includes_configured_time(segment, config) { continue; }
+                    let segment_items = calculator::filter_items_for_time_segment(segment, &base_items, &setup_config, game_data);
+                    if segment_items.is_empty() { continue; }
+                    let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+
+                    let mut probabilities = calculator::calculate_final_probabilities(&segment_items, &setup_config, game_data);
+                    if config.minigame.enabled {
+                        minigame.apply(&mut probabilities, &setup_config, game_data);
+                    }
+                    quality::apply(&mut probabilities, &setup_config, game_data, &mut minigame);
+
+                    let (value_per_cast, unpriced_prob) = value_per_cast(&probabilities, &setup_config, game_data);
+                    let casts_per_hour = casts_per_hour(&probabilities, &setup_config, game_data);
+
+                    spots.push(MoneySpot {
+                        location: location_name.clone(),
+                        fish_area: area_id.clone(),
+                        start_time: segment.0,
+                        end_time: segment.1,
+                        setup: setup_name.clone(),
+                        value_per_cast,
+                        casts_per_hour,
+                        value_per_hour: value_per_cast * casts_per_hour,
+                        unpriced_prob,
+                    });
+                }
+            }
+        }
+    }
+
+    spots.sort_by(|a, b| b.value_per_hour.total_cmp(&a.value_per_hour));
+    spots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_game_data;
+    use crate::models::ObjectData;
+    use std::path::Path;
+
+    /// 加载游戏数据，并加入一条售价为 45 的测试用鱼和一个售价相同的非鱼物品。
+    fn game_data() -> GameData {
+        let mut game_data = load_game_data(&Path::new(env!("CARGO_MANIFEST_DIR")).join("data")).expect("game data loads");
+        let object = |category| ObjectData { name: "Test".to_string(), category, price: 45 };
+        game_data.objects.insert("(O)TestFish".to_string(), object(-4));
+        game_data.objects.insert("(O)TestItem".to_string(), object(-20));
+        game_data
+    }
+
+    fn with_professions(professions: &[&str]) -> AppConfig {
+        AppConfig { professions: professions.iter().map(|p| p.to_string()).collect(), ..AppConfig::for_test() }
+    }
+
+    fn probability(display_id: &str, final_prob: f64, landed_prob: Option<f64>) -> ProbabilityDetails {
+        ProbabilityDetails {
+            display_id: display_id.to_string(),
+            name: display_id.to_string(),
+            precedence: 0,
+            get_chance_prob: final_prob,
+            bite_chance_prob: 1.0,
+            final_prob,
+            source_group_id: display_id.to_string(),
+            source: "Beach#0".to_string(),
+            catch_success_prob: None,
+            landed_prob,
+            expected_count: 1.0,
+            quality: None,
+        }
+    }
+
+    fn quality(normal: f64, silver: f64, gold: f64, iridium: f64) -> FishQuality {
+        FishQuality { normal, silver, gold, iridium, mean_size: 0.0, size_distribution: vec![] }
+    }
+
+    #[test]
+    fn angler_replaces_fisher() {
+        assert_eq!(profession_multiplier(true, &with_professions(&[])), 1.0);
+        assert_eq!(profession_multiplier(true, &with_professions(&["Fisher"])), 1.25);
+        assert_eq!(profession_multiplier(true, &with_professions(&["Angler"])), 1.5);
+        assert_eq!(profession_multiplier(true, &with_professions(&["Fisher", "Angler"])), 1.5);
+        assert_eq!(profession_multiplier(false, &with_professions(&["Fisher", "Angler"])), 1.0);
+    }
+
+    #[test]
+    fn prices_floor_quality_before_profession() {
+        let game_data = game_data();
+        let price_at = |display_id: &str, professions: &[&str], index: usize| {
+            let mut shares = [0.0; 4];
+            shares[index] = 1.0;
+            let q = quality(shares[0], shares[1], shares[2], shares[3]);
+            expected_sell_price(display_id, Some(&q), &with_professions(professions), &game_data).unwrap()
+        };
+
+        // 45 × 1 / 1.25 / 1.5 / 2 向下取整为 45 / 56 / 67 / 90，再乘以职业加成并向下取整
+        let cases: [(&str, &[&str], [f64; 4]); 4] = [
+            ("(O)TestFish", &[], [45.0, 56.0, 67.0, 90.0]),
+            ("(O)TestFish", &["Fisher"], [56.0, 70.0, 83.0, 112.0]),
+            ("(O)TestFish", &["Angler"], [67.0, 84.0, 100.0, 135.0]),
+            ("(O)TestItem", &["Angler"], [45.0, 56.0, 67.0, 90.0]),
+        ];
+        for (display_id, professions, prices) in cases {
+            for (index, price) in prices.into_iter().enumerate() {
+                assert_eq!(price_at(display_id, professions, index), price, "{} {:?} quality {}", display_id, professions, index);
+            }
+        }
+
+        // 没有品质分布时按普通品质计算；按品质概率加权
+        let fisher = with_professions(&["Fisher"]);
+        assert_eq!(expected_sell_price("(O)TestFish", None, &fisher, &game_data), Some(56.0));
+        let weighted = expected_sell_price("(O)TestFish", Some(&quality(0.4, 0.3, 0.2, 0.1)), &fisher, &game_data).unwrap();
+        assert!((weighted - (0.4 * 56.0 + 0.3 * 70.0 + 0.2 * 83.0 + 0.1 * 112.0)).abs() < 1e-9);
+        assert_eq!(expected_sell_price("(O)Unknown", None, &fisher, &game_data), None);
+    }
+
+    #[test]
+    fn unpriced_items_are_counted_separately() {
+        let game_data = game_data();
+        let probabilities = [
+            probability("(O)TestFish", 0.5, Some(0.25)),
+            probability("(O)TestItem", 0.2, None),
+            probability("(O)Unknown", 0.3, None),
+        ];
+        let (value, unpriced_prob) = value_per_cast(&probabilities, &with_professions(&["Fisher"]), &game_data);
+        // 有小游戏模拟时按 landed_prob 计算
+        assert!((value - (0.25 * 56.0 + 0.2 * 45.0)).abs() < 1e-9);
+        assert!((unpriced_prob - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn casts_per_hour_converts_game_minutes() {
+        let game_data = game_data();
+        let config = AppConfig::for_test();
+        // 钓鱼等级 10、不用鱼饵：等待咬钩 (0.6 + 30 − 2.5) / 2 = 14.05 秒
+        let nothing = 1.5 + 14.05 + 2.0;
+        assert!((casts_per_hour(&[], &config, &game_data) - 60.0 * 0.7 / nothing).abs() < 1e-9);
+        // 河豚 (O)128 需要小游戏
+        let pufferfish = 1.5 + 14.05 + 10.0;
+        let probabilities = [probability("(O)128", 1.0, None)];
+        assert!((casts_per_hour(&probabilities, &config, &game_data) - 60.0 * 0.7 / pufferfish).abs() < 1e-9);
+    }
+}
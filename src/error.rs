@@ -0,0 +1,102 @@
+//! src/error.rs
+//!
+//! 定义库对外暴露的错误类型。
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// 由用户配置转换为 AppConfig 时可能出现的错误。
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// Locations.json 中不存在该地点
+    UnknownLocation(String),
+    /// StringMap.json 中无法识别的季节
+    InvalidSeason(String),
+    /// StringMap.json 中无法识别的天气
+    InvalidWeather(String),
+    /// 既不是已知鱼饵，也无法解析为某条鱼的特制鱼饵
+    UnknownSpecificBait(String),
+    /// StringMap.json 中鱼饵对应的物品 Id 不是已知的鱼饵种类
+    UnknownBait(String),
+    /// StringMap.json 中无法识别的钓具，或其物品 Id 不是已知的钓具种类
+    UnknownTackle(String),
+    /// StringMap.json 中无法识别的钓竿，或其工具 Id 不是已知的钓竿种类
+    UnknownRod(String),
+    /// 钓竿没有鱼饵槽位，却配置了鱼饵
+    RodCannotHoldBait(String),
+    /// 装备的钓具超过钓竿的槽位数
+    TooManyTackles { rod: String, count: usize, slots: usize },
+    /// 水深超过当前钓鱼等级能抛到的最远距离
+    WaterDepthOutOfReach { depth: u32, max: u32 },
+    /// StringMap.json 中无法识别的职业
+    UnknownProfession(String),
+    /// Fish.json 与 StringMap.json 中都找不到该鱼
+    UnknownFish(String),
+    /// TreasureLoot.json 中继续抽取的概率不在 [0, 1) 之间
+    InvalidRollChance { field: String, value: f64 },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownLocation(name) => write!(f, "无法识别的地点: {}", name),
+            ConfigError::InvalidSeason(season) => write!(f, "Invalid season: {}", season),
+            ConfigError::InvalidWeather(weather) => write!(f, "Invalid weather: {}", weather),
+            ConfigError::UnknownSpecificBait(bait) => write!(f, "无法识别的特制鱼饵: {}", bait),
+            ConfigError::UnknownBait(item_id) => write!(f, "无法识别的鱼饵物品: {}", item_id),
+            ConfigError::UnknownTackle(name) => write!(f, "无法识别的钓具: {}", name),
+            ConfigError::UnknownRod(name) => write!(f, "无法识别的钓竿: {}", name),
+            ConfigError::RodCannotHoldBait(rod) => write!(f, "{} 不能装备鱼饵", rod),
+            ConfigError::TooManyTackles { rod, count, slots } => {
+                write!(f, "{} 只能装备 {} 个钓具，配置中有 {} 个", rod, slots, count)
+            }
+            ConfigError::WaterDepthOutOfReach { depth, max } => {
+                write!(f, "水深 {} 超过了当前钓鱼等级能达到的最大水深 {}", depth, max)
+            }
+            ConfigError::UnknownProfession(name) => write!(f, "无法识别的职业: {}", name),
+            ConfigError::UnknownFish(name) => write!(f, "无法识别的鱼: {}", name),
+            ConfigError::InvalidRollChance { field, value } => {
+                write!(f, "TreasureLoot.json 中的 {} 必须在 [0, 1) 之间，实际为 {}", field, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// 加载配置文件与游戏数据时可能出现的错误。
+#[derive(Debug)]
+pub enum Error {
+    /// 读取文件失败
+    Io { path: PathBuf, source: std::io::Error },
+    /// 文件内容不是合法的 JSON，或与期望的结构不符
+    Json { path: PathBuf, source: serde_json::Error },
+    /// 配置内容无效
+    Config(ConfigError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "无法读取 {}: {}", path.display(), source),
+            Error::Json { path, source } => write!(f, "无法解析 {}: {}", path.display(), source),
+            Error::Config(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Json { source, .. } => Some(source),
+            Error::Config(e) => Some(e),
+        }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        Error::Config(e)
+    }
+}
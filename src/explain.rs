@@ -0,0 +1,172 @@
+//! src/explain.rs
+//!
+//! 解释模式：追踪某个物品在计算过程中经过的每一个判断。
+//!
+//! calculator.rs 中的解析、时间段过滤与成功率计算都接受一个可选的 `ExplainTrace`，
+//! 遇到与目标物品有关的条目时记录每项过滤条件是否通过、Condition 中各子条件的求值结果、
+//! LOCATION_FISH 的继承链，以及 GetChance 与 BiteChance 公式中的每一项。
+//! 未启用解释模式时传入 `None`，不会产生任何额外开销。
+
+use crate::calculator;
+use crate::models::{AppConfig, Bait, GameData, ResolvedItem, SpawnFishData, SpawnSource};
+use crate::utils;
+use serde::Serialize;
+
+/// 追踪记录中的一行，`depth` 为缩进层级。
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceLine {
+    pub depth: usize,
+    pub text: String,
+}
+
+/// 一个 FishArea 的追踪记录。
+#[derive(Debug, Clone, Serialize)]
+pub struct AreaExplanation {
+    pub location: String,
+    pub fish_area: Option<String>,
+    pub lines: Vec<TraceLine>,
+}
+
+/// 收集与目标物品有关的决策记录。
+#[derive(Debug)]
+pub struct ExplainTrace {
+    fish_id: String,
+    depth: usize,
+    lines: Vec<TraceLine>,
+}
+
+impl ExplainTrace {
+    pub fn new(fish_id: &str) -> Self {
+        Self { fish_id: fish_id.to_string(), depth: 0, lines: Vec::new() }
+    }
+
+    /// 条目是否与目标物品有关：直接产出该物品、在 RandomItemId 中包含该物品，或者是 LOCATION_FISH 继承。
+    pub fn wants(&self, spawn_data: &SpawnFishData) -> bool {
+        match &spawn_data.item_id {
+            Some(id) => *id == self.fish_id || id.starts_with("LOCATION_FISH"),
+            None => spawn_data.random_item_id.as_ref().is_some_and(|ids| ids.contains(&self.fish_id)),
+        }
+    }
+
+    pub fn is_target(&self, display_id: &str) -> bool {
+        self.fish_id == display_id
+    }
+
+    pub fn line(&mut self, text: impl Into<String>) {
+        self.lines.push(TraceLine { depth: self.depth, text: text.into() });
+    }
+
+    pub fn indent(&mut self) {
+        self.depth += 1;
+    }
+
+    pub fn outdent(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// 记录开始检查一个刷鱼条目。
+    pub fn entry(&mut self, source: SpawnSource, spawn_data: &SpawnFishData) {
+        let item = spawn_data.item_id.clone()
+            .or_else(|| spawn_data.random_item_id.as_ref().map(|ids| format!("RandomItemId [{}]", ids.join(", "))))
+            .unwrap_or_default();
+        let id = spawn_data.id.as_deref().filter(|id| *id != item).map_or(String::new(), |id| format!(" ({})", id));
+        self.line(format!("条目 {}{}：{}，Precedence {}", source, id, item, spawn_data.precedence));
+    }
+
+    pub fn into_lines(self) -> Vec<TraceLine> {
+        self.lines
+    }
+}
+
+/// 记录一项检查的结果并原样返回。`trace` 为 `None` 时不会调用 `describe`。
+pub fn check(trace: &mut Option<&mut ExplainTrace>, passed: bool, describe: impl FnOnce() -> String) -> bool {
+    if let Some(t) = trace.as_deref_mut() {
+        t.indent();
+        t.line(format!("[{}] {}", if passed { "PASS" } else { "FAIL" }, describe()));
+        t.outdent();
+    }
+    passed
+}
+
+/// 记录一条说明。`trace` 为 `None` 时不会调用 `describe`。
+pub fn note(trace: &mut Option<&mut ExplainTrace>, describe: impl FnOnce() -> String) {
+    if let Some(t) = trace.as_deref_mut() {
+        t.indent();
+        t.line(describe());
+        t.outdent();
+    }
+}
+
+/// 对配置中地点的每个 FishArea，追踪目标物品从解析、时间段过滤到成功率与最终概率的全过程。
+pub fn explain_fish(fish_id: &str, config: &AppConfig, game_data: &GameData) -> Vec<AreaExplanation> {
+    let mut explanations = Vec::new();
+
+    for area_id in calculator::get_fish_area_ids(&config.location_name, game_data) {
+        let mut trace = ExplainTrace::new(fish_id);
+        trace.line("解析刷鱼条目");
+        trace.indent();
+        let base_items = calculator::resolve_fish_list_traced(config, game_data, &area_id, Some(&mut trace));
+        trace.outdent();
+
+        if base_items.iter().any(|item| item.display_id == fish_id) {
+            for segment in utils::calculate_time_segments(&base_items, game_data) {
+                if !utils::includes_configured_time(segment, config) { continue; }
+                explain_segment(segment, &base_items, config, game_data, &mut trace);
+            }
+        } else {
+            trace.line("该 FishArea 中没有可以钓到该物品的条目");
+        }
+
+        explanations.push(AreaExplanation {
+            location: config.location_name.clone(),
+            fish_area: area_id,
+            lines: trace.into_lines(),
+        });
+    }
+
+    explanations
+}
+
+/// 追踪一个时间段内的过滤、成功率与最终概率。
+fn explain_segment(
+    segment: (u32, u32),
+    base_items: &[ResolvedItem],
+    config: &AppConfig,
+    game_data: &GameData,
+    trace: &mut ExplainTrace,
+) {
+    trace.line(format!("时间段 {} - {}", segment.0, segment.1));
+    trace.indent();
+
+    let segment_items = calculator::filter_items_for_time_segment_traced(segment, base_items, config, game_data, Some(&mut *trace));
+    let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+
+    let targets: Vec<&ResolvedItem> = segment_items.iter().copied().filter(|item| trace.is_target(&item.display_id)).collect();
+    if targets.is_empty() {
+        trace.line("该物品不在此时间段的候选物品中，概率为 0");
+        trace.outdent();
+        return;
+    }
+    for item in targets {
+        trace.line(format!("{} 的成功率", item.source));
+        calculator::success_rates_traced(item, config, game_data, Some(&mut *trace));
+    }
+
+    let final_prob: f64 = calculator::calculate_final_probabilities(&segment_items, config, game_data).iter()
+        .filter(|p| trace.is_target(&p.display_id))
+        .map(|p| p.final_prob)
+        .sum();
+    let passes = if config.bait_target_fish_id.is_some() {
+        format!("特制鱼饵，最多遍历 {} 轮", Bait::Targeted.spawn_passes())
+    } else {
+        format!("遍历 {} 轮", config.bait().map_or(1, Bait::spawn_passes))
+    };
+    trace.line(format!(
+        "最终概率 {:.4}%（与 {} 个物品按 Precedence 竞争，{}）",
+        final_prob * 100.0,
+        segment_items.len(),
+        passes
+    ));
+    trace.outdent();
+}
+
@@ -0,0 +1,272 @@
+//! src/gsq.rs
+//!
+//! 游戏状态查询 (Game State Query, GSQ) 的解析与求值。
+//!
+//! 语法与游戏一致：
+//! - 多个查询以逗号分隔，全部为真时条件成立；
+//! - 查询以 `!` 开头表示取反；
+//! - 参数以空格分隔，双引号包裹的参数可以包含空格和逗号，`\` 用于转义；
+//! - `ANY "查询1" "查询2" ...` 中任意一个子条件成立即为真。
+//!
+//! 查询从 AppConfig 中的结构化状态 (GameState) 读取数据。
+//! 无法识别的查询会回退到 `config.conditions` 中查找 `"true"`。
+//!
+//! 求值结果是条件成立的概率，而不是布尔值：确定性的查询为 0 或 1，
+//! `RANDOM` 查询为其概率。游戏每次抛竿都会重新掷骰，各查询视为相互独立：
+//! 逗号连接的查询相乘，取反为 `1 - p`，`ANY` 为 `1 - Π(1 - p)`。
+
+use crate::models::AppConfig;
+
+/// 被动节日（Data/PassiveFestivals）每天的开始时间。未列出的节日视为全天开放。
+pub const PASSIVE_FESTIVAL_START_TIMES: [(&str, u32); 3] = [
+    ("TroutDerby", 610),
+    ("SquidFest", 610),
+    ("NightMarket", 1700),
+];
+
+fn passive_festival_start_time(id: &str) -> u32 {
+    PASSIVE_FESTIVAL_START_TIMES.iter()
+        .find(|(festival, _)| *festival == id)
+        .map_or(600, |(_, start)| *start)
+}
+
+/// 计算一个 Condition 字符串成立的概率。`None` 或空字符串视为必然成立。
+pub fn check_condition(condition_str: &Option<String>, config: &AppConfig) -> f64 {
+    let Some(condition) = condition_str else { return 1.0; };
+    check_condition_str(condition, config)
+}
+
+/// 计算一个 Condition 字符串中所有查询同时成立的概率。
+pub fn check_condition_str(condition: &str, config: &AppConfig) -> f64 {
+    split_queries(condition).iter().map(|query| evaluate_query(query, config)).product()
+}
+
+/// 按逗号拆分查询，忽略引号内的逗号。
+pub fn split_queries(condition: &str) -> Vec<String> {
+    split_quote_aware(condition, ',', false)
+        .into_iter()
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty())
+        .collect()
+}
+
+/// 按空格拆分查询参数，去除引号并处理转义。
+pub fn split_args(query: &str) -> Vec<String> {
+    split_quote_aware(query, ' ', true)
+        .into_iter()
+        .filter(|arg| !arg.is_empty())
+        .collect()
+}
+
+/// 按分隔符拆分字符串，引号内的分隔符不生效。
+/// `unquote` 为 true 时去除引号与转义符，否则保留原文，以便后续再次拆分。
+fn split_quote_aware(input: &str, delimiter: char, unquote: bool) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if !unquote { current.push(c); }
+                if let Some(escaped) = chars.next() { current.push(escaped); }
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                if !unquote { current.push(c); }
+            }
+            c if c == delimiter && !in_quotes => parts.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    parts.push(current);
+    parts
+}
+
+/// 求值单个查询（可带 `!` 前缀），返回其成立的概率。
+pub fn evaluate_query(query: &str, config: &AppConfig) -> f64 {
+    let (is_negated, trimmed_query) = match query.trim().strip_prefix('!') {
+        Some(q) => (true, q.trim_start()),
+        None => (false, query.trim()),
+    };
+
+    let args = split_args(trimmed_query);
+    let Some(key) = args.first() else { return 1.0; };
+    let args: Vec<&str> = args[1..].iter().map(String::as_str).collect();
+
+    // 没有具体时间时（解析阶段），与时间有关的查询结果未知：无论是否取反都保留条目，
+    // 留给 `filter_items_for_time_segment` 按时间段判断
+    if config.game_state.time_of_day.is_none() && depends_on_time_of_day(key, &args, config) {
+        return 1.0;
+    }
+
+    let probability = match key.to_uppercase().as_str() {
+        "ANY" => 1.0 - args.iter().map(|condition| 1.0 - check_condition_str(condition, config)).product::<f64>(),
+        "RANDOM" => {
+            let chance = parse_arg::<f64>(&args, 0).unwrap_or(0.0);
+            let add_daily_luck = args.iter().any(|a| a.eq_ignore_ascii_case("@addDailyLuck"));
+            (if add_daily_luck { chance + config.daily_luck } else { chance }).clamp(0.0, 1.0)
+        }
+        _ => {
+            let result = match evaluate_known_query(key, &args, config) {
+                Some(result) => result,
+                // 默认行为：检查条件是否存在且为 "true"
+                None => config.conditions.get(trimmed_query).is_some_and(|v| v == "true"),
+            };
+            if result { 1.0 } else { 0.0 }
+        }
+    };
+
+    if is_negated { 1.0 - probability } else { probability }
+}
+
+/// 查询的结果是否取决于一天中的时间：TIME、当天开放的被动节日的 IS_PASSIVE_FESTIVAL_OPEN，
+/// 以及包含这些查询的 ANY。
+fn depends_on_time_of_day(key: &str, args: &[&str], config: &AppConfig) -> bool {
+    match key.to_uppercase().as_str() {
+        "TIME" => true,
+        "IS_PASSIVE_FESTIVAL_OPEN" => args.first().is_some_and(|id| config.game_state.passive_festivals.contains(*id)),
+        "ANY" => args.iter().flat_map(|condition| split_queries(condition)).any(|query| {
+            let args = split_args(query.strip_prefix('!').unwrap_or(&query).trim_start());
+            let sub_args: Vec<&str> = args.iter().skip(1).map(String::as_str).collect();
+            args.first().is_some_and(|key| depends_on_time_of_day(key, &sub_args, config))
+        }),
+        _ => false,
+    }
+}
+
+/// 确定性查询的调度中心。返回 `None` 表示不认识该查询。
+fn evaluate_known_query(key: &str, args: &[&str], config: &AppConfig) -> Option<bool> {
+    let state = &config.game_state;
+
+    let result = match key.to_uppercase().as_str() {
+        "TRUE" => true,
+        "FALSE" => false,
+        "SEASON" => args.iter().any(|s| s.eq_ignore_ascii_case(&config.season)),
+        // 第一个参数为地点（Here/Target/地点名），只模拟当前地点
+        "LOCATION_SEASON" => args.iter().skip(1).any(|s| s.eq_ignore_ascii_case(&config.season)),
+        "WEATHER" => {
+            let weather_id = state.weather_id(&config.weather);
+            args.iter().skip(1).any(|w| w.eq_ignore_ascii_case(weather_id))
+        }
+        "TIME" => {
+            // 没有具体时间的情况已由 evaluate_query 处理
+            let Some(time) = state.time_of_day else { return Some(true); };
+            let min = parse_arg(args, 0).unwrap_or(i64::MIN);
+            let max = parse_arg(args, 1).unwrap_or(i64::MAX);
+            (min..=max).contains(&(time as i64))
+        }
+        "YEAR" => {
+            let min = parse_arg(args, 0).unwrap_or(1);
+            let max = parse_arg(args, 1).unwrap_or(i64::MAX);
+            (min..=max).contains(&(state.year as i64))
+        }
+        "DAY_OF_MONTH" => args.iter().any(|d| d.parse::<u32>().ok() == Some(state.day_of_month)),
+        "PLAYER_HAS_MAIL" => args.get(1).is_some_and(|mail| state.mail_received.contains(*mail)),
+        "PLAYER_HAS_ITEM" => args.get(1).is_some_and(|item| state.has_item(item)),
+        "PLAYER_SPECIAL_ORDER_RULE_ACTIVE" => args.get(1).is_some_and(|rule| state.special_order_rules.contains(*rule)),
+        "IS_PASSIVE_FESTIVAL_OPEN" => args.first().is_some_and(|id| {
+            state.passive_festivals.contains(*id)
+                && state.time_of_day.is_none_or(|time| time >= passive_festival_start_time(id))
+        }),
+        "IS_FESTIVAL_DAY" => state.is_festival_day,
+        "IS_ISLAND_NORTH_BRIDGE_FIXED" => state.island_north_bridge_fixed,
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// 收集 Condition 中所有与一天中时间相关的查询所产生的时间段边界。
+/// 边界 t 表示从 t 开始（含）条件的结果可能发生变化。
+pub fn time_boundaries(condition: &str) -> Vec<u32> {
+    let mut boundaries = Vec::new();
+    for query in split_queries(condition) {
+        let query = query.strip_prefix('!').unwrap_or(&query).trim_start();
+        let args = split_args(query);
+        let Some(key) = args.first() else { continue; };
+        let args: Vec<&str> = args[1..].iter().map(String::as_str).collect();
+
+        match key.to_uppercase().as_str() {
+            "ANY" => boundaries.extend(args.iter().flat_map(|sub| time_boundaries(sub))),
+            "TIME" => {
+                // TIME min max 为闭区间，max 之后的下一个 10 分钟才不再满足
+                if let Some(min) = parse_arg::<u32>(&args, 0) { boundaries.push(min); }
+                if let Some(max) = parse_arg::<u32>(&args, 1) { boundaries.push(add_ten_minutes(max)); }
+            }
+            "IS_PASSIVE_FESTIVAL_OPEN" => {
+                if let Some(id) = args.first() { boundaries.push(passive_festival_start_time(id)); }
+            }
+            _ => {}
+        }
+    }
+    boundaries
+}
+
+/// 在 HHMM 格式的时间上加 10 分钟。
+fn add_ten_minutes(time: u32) -> u32 {
+    let next = time + 10;
+    if next % 100 >= 60 { next + 40 } else { next }
+}
+
+fn parse_arg<T: std::str::FromStr>(args: &[&str], index: usize) -> Option<T> {
+    args.get(index).and_then(|s| s.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_queries_outside_quotes() {
+        assert_eq!(
+            split_queries(r#"ANY "SEASON spring, YEAR 2" "WEATHER Here Rain", !IS_FESTIVAL_DAY"#),
+            vec![r#"ANY "SEASON spring, YEAR 2" "WEATHER Here Rain""#, "!IS_FESTIVAL_DAY"],
+        );
+    }
+
+    #[test]
+    fn collects_time_boundaries() {
+        assert_eq!(
+            time_boundaries(r#"IS_PASSIVE_FESTIVAL_OPEN SquidFest, ANY "TIME 0600 1850" "!TIME 2000""#),
+            vec![610, 600, 1900, 2000],
+        );
+    }
+
+    #[test]
+    fn random_queries_are_probabilities() {
+        let config = AppConfig::for_test();
+        assert!((check_condition_str("RANDOM 0.4", &config) - 0.4).abs() < 1e-12);
+        assert!((check_condition_str("!RANDOM 0.4", &config) - 0.6).abs() < 1e-12);
+        // ANY 成立的概率为 1 - Π(1 - p)
+        assert!((check_condition_str(r#"ANY "RANDOM 0.4" "RANDOM 0.5""#, &config) - 0.7).abs() < 1e-12);
+        // 逗号分隔的查询同时成立
+        assert!((check_condition_str("RANDOM 0.4, RANDOM 0.5", &config) - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn time_queries_are_kept_without_a_time() {
+        let mut config = AppConfig::for_test();
+        config.game_state.passive_festivals.insert("SquidFest".to_string());
+        for condition in ["TIME 0600 1800", "!TIME 2000", r#"!ANY "TIME 0600 1800" "FALSE""#, "!IS_PASSIVE_FESTIVAL_OPEN SquidFest"] {
+            assert_eq!(check_condition_str(condition, &config), 1.0, "{}", condition);
+        }
+        // 没有开放的节日与时间无关，取反照常生效
+        assert_eq!(check_condition_str("!IS_PASSIVE_FESTIVAL_OPEN TroutDerby", &config), 1.0);
+        assert_eq!(check_condition_str("IS_PASSIVE_FESTIVAL_OPEN TroutDerby", &config), 0.0);
+
+        config.game_state.time_of_day = Some(2000);
+        assert_eq!(check_condition_str("!TIME 2000", &config), 0.0);
+        assert_eq!(check_condition_str("!IS_PASSIVE_FESTIVAL_OPEN SquidFest", &config), 0.0);
+        config.game_state.time_of_day = Some(1000);
+        assert_eq!(check_condition_str("!TIME 2000", &config), 1.0);
+    }
+
+    #[test]
+    fn splits_args_with_quotes_and_escapes() {
+        assert_eq!(
+            split_args(r#"ANY "SEASON spring, YEAR 2" "PLAYER_HAS_MAIL Current \"x y\"""#),
+            vec!["ANY", "SEASON spring, YEAR 2", r#"PLAYER_HAS_MAIL Current "x y""#],
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! src/lib.rs
+//!
+//! 星露谷物语钓鱼概率计算库。
+//!
+//! 典型的调用流程：
+//! 1. `load_game_data` 加载游戏数据，`load_user_config` + `build_app_config` 构建配置；
+//! 2. `get_fish_area_ids` 列出地点的所有 FishArea，`get_resolved_fish_list` 解析出可钓物品；
+//! 3. `calculate_time_segments` 切分时间段，`filter_items_for_time_segment` 做时间段过滤；
+//! 4. `calculate_final_probabilities` 计算每个物品的最终概率。
+//!
+//! `report::build_location_report` 将以上步骤串联起来，生成一个地点的完整结构化报告；
+//! 启用 `minigame` 模拟后，报告中还会给出咬钩后成功钓起的概率，`quality` 给出尺寸与品质分布，
+//! `treasure` 给出宝箱概率与战利品的期望数量；
+//! `search::search_fish` 反向查询某条鱼的最佳地点、时间与装备；
+//! `summary` 按时段时长将结果汇总为全天、全季的期望概率；
+//! `timing` 将概率换算为期望抛竿次数与耗时，`economy` 进一步换算为每次抛竿与每小时的期望收益；
+//! `explain` 追踪某个物品在解析、过滤与成功率计算中经过的每一个判断；
+//! `position` 列出限制玩家或浮标位置的刷鱼条目；
+//! `simulation` 用蒙特卡洛模拟逐次复刻刷鱼循环，验证确定性模型的结果；
+//! `crab_pot::build_crab_pot_report` 计算蟹笼的每日捕获概率。
+
+pub mod calculator;
+pub mod config;
+pub mod crab_pot;
+pub mod economy;
+pub mod error;
+pub mod explain;
+pub mod gsq;
+pub mod minigame;
+pub mod models;
+pub mod position;
+pub mod quality;
+pub mod report;
+pub mod search;
+pub mod simulation;
+pub mod summary;
+pub mod timing;
+pub mod treasure;
+pub mod utils;
+
+pub use calculator::{
+    bite_exclusion, calculate_final_probabilities, filter_items_for_time_segment, get_fish_area_ids,
+    get_resolved_fish_list, get_resolved_item_name, resolve_fish_list, segment_exclusion,
+};
+pub use config::{build_app_config, load_and_build_config, load_game_data, load_user_config};
+pub use error::{ConfigError, Error};
+pub use models::{AppConfig, Bait, CastTimingModel, ExcludedEntry, ExclusionReason, FishListResolution, FishQuality, MinigameModel, ConfigOverrides, GameData, GameState, ProbabilityDetails, ResolvedItem, Rod, Tackle, UserConfigRaw};
+pub use utils::calculate_time_segments;
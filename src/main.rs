@@ -1,239 +1,101 @@
 //! src/main.rs
 
-use std::collections::{HashMap, HashSet};
+use stardew_fishing_prob::{config, crab_pot, economy, explain, position, report, search, simulation, summary, timing};
 
-mod config;
-mod models;
-mod calculator;
-mod utils;
+mod cli;
+mod output;
 
 fn main() {
+    // 0. 解析命令行参数
+    let cli_args = match cli::parse_env_args() {
+        Ok(cli::CliCommand::Run(args)) => args,
+        Ok(cli::CliCommand::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("错误：{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
     // 1. 加载所有配置和游戏数据
-    let (app_config, game_data) = match config::load_and_build_config() {
+    let (app_config, game_data) = match config::load_and_build_config(
+        &cli_args.config_path,
+        &cli_args.data_dir,
+        &cli_args.overrides,
+    ) {
         Ok((config, data)) => (config, data),
         Err(e) => {
             eprintln!("\n错误：加载配置失败。\n原因: {}", e);
-            return;
+            std::process::exit(1);
         }
     };
     
-    // 2. 获取并准备遍历所有 FishAreas
-    let location_data = game_data.locations.get(&app_config.location_name)
-        .expect("Location data should exist");
-    
-    let mut fish_area_ids: Vec<Option<String>> = location_data.fish_areas.keys().cloned().map(Some).collect();
-    let has_default_area_fish = location_data.fish.iter().any(|f| f.fish_area_id.is_none()) 
-                             || game_data.locations["Default"].fish.iter().any(|f| f.fish_area_id.is_none());
-    if has_default_area_fish {
-        if !fish_area_ids.contains(&None) { fish_area_ids.push(None); }
+    // 2. 解释模式：追踪单个物品的计算过程
+    if let Some(fish_query) = cli_args.explain.as_deref() {
+        let fish_id = resolve_fish_or_exit(fish_query, &game_data);
+        let explanations = explain::explain_fish(&fish_id, &app_config, &game_data);
+        output::print_explanations(&fish_id, &explanations, cli_args.output);
+        return;
     }
 
-    // 3. 主逻辑
-    for area_id in fish_area_ids {
-        let base_items = calculator::get_resolved_fish_list(&app_config, &game_data, &area_id, false);
-        let time_segments = utils::calculate_time_segments(&base_items, &game_data);
-        
-        for segment in time_segments {
-            let segment_items = calculator::filter_items_for_time_segment(segment, &base_items, &app_config, &game_data);
-            if segment_items.is_empty() { continue; }
-
-            // 根据是否为魔法鱼饵选择不同的输出模式
-            if app_config.bait_item_id.as_deref() == Some("(O)908") {
-                // --- 魔法鱼饵的简单列表输出 ---
-                // <<< MODIFIED: Pass the 'segment' tuple to the function
-                run_magic_bait_scenario(segment, &segment_items, &app_config, &game_data, &area_id);
-            } else {
-                // --- 其他鱼饵的多列对比表格输出 ---
-                // <<< MODIFIED: Pass the 'segment' tuple to the function
-                run_comparison_scenario(segment, &segment_items, &app_config, &game_data, &area_id);
-            }
+    // 3. 按计算模式生成结果并输出
+    match cli_args.mode {
+        cli::RunMode::Fishing => {
+            let reports = report::build_location_report(&app_config, &game_data);
+            output::print_reports(&reports, cli_args.output, cli_args.show_quality, cli_args.show_treasure, cli_args.show_unavailable);
         }
-    }
-}
-
-/// 运行并打印魔法鱼饵的单列输出
-fn run_magic_bait_scenario<'a>(
-    // <<< MODIFIED: Add 'time_segment' as an argument
-    time_segment: (u32, u32),
-    segment_items: &[&'a models::ResolvedItem<'a>],
-    app_config: &models::AppConfig,
-    game_data: &models::GameData,
-    area_id: &Option<String>
-) {
-    let area_name = if let Some(id) = area_id { id.as_str() } else { "Default" };
-    // <<< MODIFIED: Update the title to include the time segment
-    println!(
-        "\nLocation: {} ({}) | Time: {} - {} | Magic Bait Mode",
-        app_config.location_name, area_name, time_segment.0, time_segment.1
-    );
-
-    let detailed_probabilities = calculator::calculate_final_probabilities(segment_items, app_config, game_data);
-    let prob_map: HashMap<String, f64> = detailed_probabilities.into_iter()
-        .map(|p| (p.display_id, p.final_prob))
-        .collect();
-
-    let mut row_items = segment_items.to_vec();
-    row_items.sort_by_key(|item| item.source_data.precedence);
-
-    // 聚合垃圾项
-    const TRASH_GROUP_SOURCE_ID: &str = "(O)167|(O)168|(O)169|(O)170|(O)171|(O)172";
-    let mut aggregated_rows: Vec<(String, String, i32, f64)> = Vec::new();
-    let mut trash_aggregator: Option<(String, String, i32, f64)> = None;
-    let mut handled_source_data: HashSet<*const models::SpawnFishData> = HashSet::new();
-
-    for item in &row_items {
-        let source_ptr = item.source_data as *const _;
-        if handled_source_data.contains(&source_ptr) { continue; }
-
-        if item.source_data.id.as_deref() == Some(TRASH_GROUP_SOURCE_ID) {
-            if trash_aggregator.is_none() {
-                trash_aggregator = Some(("Trash Group".to_string(), "Trash".to_string(), item.source_data.precedence, 0.0));
-            }
-            for sibling in row_items.iter().filter(|i| i.source_data as *const _ == source_ptr) {
-                if let Some(prob) = prob_map.get(&sibling.display_id) {
-                    if let Some((_, _, _, agg_prob)) = &mut trash_aggregator { *agg_prob += prob; }
-                }
-            }
-        } else {
-            let prob = prob_map.get(&item.display_id).cloned().unwrap_or(0.0);
-            let name = calculator::get_resolved_item_name(item, game_data);
-            aggregated_rows.push((item.display_id.clone(), name, item.source_data.precedence, prob));
+        cli::RunMode::Day => {
+            let summaries = summary::summarize_day(&app_config, &game_data);
+            output::print_summaries(&summaries, cli_args.output);
         }
-        handled_source_data.insert(source_ptr);
-    }
-
-    if let Some(agg_trash) = trash_aggregator { aggregated_rows.push(agg_trash); }
-    aggregated_rows.sort_by_key(|(_, _, prio, _)| *prio);
-
-    // 打印简化的表格
-    println!("{:<15} | {:<5} | {}", "Name", "Prio", "Final Prob");
-    println!("{:-<15}-+-{:-<7}-+-{:-<15}", "", "", "");
-
-    for (_, name, prio, prob) in &aggregated_rows {
-        println!(
-            "{:<15} | {:<5} | {:>12.2}%",
-            utils::truncate_string(name, 13),
-            prio,
-            prob * 100.0
-        );
-    }
-}
-
-/// 运行并打印多列对比表格
-fn run_comparison_scenario<'a>(
-    time_segment: (u32, u32),
-    segment_items: &[&'a models::ResolvedItem<'a>],
-    app_config: &models::AppConfig,
-    game_data: &models::GameData,
-    area_id: &Option<String>
-) {
-    let area_name = if let Some(id) = area_id { id.as_str() } else { "Default" };
-    println!(
-        "\nLocation: {} ({}) | Time: {} - {} | Comparison Mode",
-        app_config.location_name, area_name, time_segment.0, time_segment.1
-    );
-    
-    let mut row_items = segment_items.to_vec();
-    row_items.sort_by_key(|item| item.source_data.precedence);
-    
-    let mut scenarios = Vec::new();
-    
-    let mut standard_config = app_config.clone();
-    standard_config.bait_item_id = None;
-    standard_config.bait_target_fish_id = None;
-    scenarios.push(("Standard".to_string(), standard_config.clone()));
-
-    let mut training_rod_config = app_config.clone();
-    training_rod_config.is_training_rod = true;
-    training_rod_config.bait_item_id = None;
-    training_rod_config.bait_target_fish_id = None;
-    scenarios.push(("TrainingRod".to_string(), training_rod_config));
-
-    // --- 在这里添加过滤逻辑 ---
-    let mut bait_fish_scenarios = Vec::new();
-    let mut handled_baits = HashSet::new();
-    for &item in segment_items {
-        // 步骤1：检查这是否是一个在 Fish.json 中定义的物品，并且我们还没有为它创建过鱼饵场景
-        if game_data.fish.contains_key(&item.display_id) && handled_baits.insert(item.display_id.clone()) {
-            
-            // 获取物品的英文名
-            let fish_name_en = &game_data.fish[&item.display_id].name;
-
-            // <<< MODIFIED: 步骤2：增加一个判断，如果物品是绿藻或白藻，则跳过，不为它创建特制鱼饵场景
-            if fish_name_en == "Green Algae" || fish_name_en == "White Algae" || fish_name_en == "Seaweed"{
-                continue; // 跳过藻类
-            }
-
-            // 步骤3：如果不是藻类，则正常创建特制鱼饵场景
-            let mut bait_config = app_config.clone();
-            bait_config.bait_item_id = Some("(O)SpecificBait".to_string());
-            bait_config.bait_target_fish_id = Some(item.display_id.clone());
-            bait_config.using_good_bait = true;
-            bait_fish_scenarios.push((fish_name_en.clone(), bait_config));
+        cli::RunMode::Season => {
+            let summaries = summary::summarize_season(&app_config, &game_data);
+            output::print_summaries(&summaries, cli_args.output);
         }
-    }
-    // --- 过滤逻辑结束 ---
-
-    bait_fish_scenarios.sort_by_key(|(_name, cfg)| {
-        segment_items.iter().find(|item| &item.display_id == cfg.bait_target_fish_id.as_ref().unwrap())
-        .map_or(i32::MAX, |item| item.source_data.precedence)
-    });
-    scenarios.extend(bait_fish_scenarios);
-
-    let mut results_map: HashMap<String, Vec<f64>> = HashMap::new();
-    for (_, scenario_config) in &scenarios {
-        let scenario_probs = calculator::calculate_final_probabilities(segment_items, scenario_config, game_data);
-        let scenario_probs_map: HashMap<String, f64> = scenario_probs.into_iter()
-            .map(|p| (p.display_id, p.final_prob)).collect();
-        for item in &row_items {
-            let prob = scenario_probs_map.get(&item.display_id).cloned().unwrap_or(0.0);
-            results_map.entry(item.display_id.clone()).or_default().push(prob);
+        cli::RunMode::Search => {
+            let fish_id = resolve_fish_or_exit(cli_args.fish.as_deref().unwrap_or_default(), &game_data);
+            let mut results = search::search_fish(&fish_id, &app_config, &game_data);
+            results.truncate(cli_args.top);
+            output::print_search_results(&fish_id, &results, cli_args.output);
         }
-    }
-    
-    const TRASH_GROUP_SOURCE_ID: &str = "(O)167|(O)168|(O)169|(O)170|(O)171|(O)172";
-    let mut aggregated_rows: Vec<(String, i32, Vec<f64>)> = Vec::new();
-    let mut trash_aggregator: Option<(String, i32, Vec<f64>)> = None;
-    let mut handled_source_data: HashSet<*const models::SpawnFishData> = HashSet::new();
-
-    for item in &row_items {
-        let source_ptr = item.source_data as *const _;
-        if handled_source_data.contains(&source_ptr) { continue; }
-
-        let item_name = calculator::get_resolved_item_name(item, game_data);
-        
-        if item.source_data.id.as_deref() == Some(TRASH_GROUP_SOURCE_ID) {
-            if trash_aggregator.is_none() {
-                trash_aggregator = Some(("Trash Group".to_string(), item.source_data.precedence, vec![0.0; scenarios.len()]));
+        cli::RunMode::Timing => {
+            let mut reports = timing::build_timing_report(&app_config, &game_data);
+            if let Some(fish_query) = cli_args.fish.as_deref() {
+                let fish_id = resolve_fish_or_exit(fish_query, &game_data);
+                reports.iter_mut().for_each(|r| r.items.retain(|item| item.display_id == fish_id));
+                reports.retain(|r| !r.items.is_empty());
             }
-            for sibling in row_items.iter().filter(|i| i.source_data as *const _ == source_ptr) {
-                if let Some(probs) = results_map.get(&sibling.display_id) {
-                    if let Some((_, _, agg_probs)) = &mut trash_aggregator {
-                        for (i, prob) in probs.iter().enumerate() { agg_probs[i] += prob; }
-                    }
-                }
-            }
-        } else {
-            let probs = results_map.get(&item.display_id).cloned().unwrap_or_default();
-            aggregated_rows.push((item_name, item.source_data.precedence, probs));
+            output::print_timing_reports(&reports, cli_args.output);
+        }
+        cli::RunMode::Money => {
+            let mut spots = economy::rank_money_spots(&app_config, &game_data);
+            spots.truncate(cli_args.top);
+            output::print_money_spots(&spots, cli_args.output);
+        }
+        cli::RunMode::Positions => {
+            let restrictions = position::list_position_restrictions(&app_config, &game_data);
+            output::print_position_restrictions(&app_config, &restrictions, cli_args.output);
+        }
+        cli::RunMode::Simulate => {
+            let reports = simulation::build_simulation_report(&app_config, &game_data, cli_args.casts, cli_args.seed);
+            output::print_simulation_reports(&reports, cli_args.output);
+        }
+        cli::RunMode::CrabPot => {
+            let reports = crab_pot::build_crab_pot_report(&app_config, &game_data);
+            output::print_crab_pot_reports(&reports, cli_args.output);
         }
-        handled_source_data.insert(source_ptr);
-    }
-
-    if let Some(agg_trash) = trash_aggregator { aggregated_rows.push(agg_trash); }
-    aggregated_rows.sort_by_key(|(_, prio, _)| *prio);
-    
-    print!("{:<15}|{:<6}|", "Item", "Prio");
-    for (name, _) in &scenarios {
-        print!("{:<12}|", utils::truncate_string(name, 10));
     }
-    println!();
+}
 
-    for (name, prio, probs) in &aggregated_rows {
-        print!("{:<15}| {:<5}|", utils::truncate_string(name, 13), prio);
-        for prob in probs {
-            print!(" {:>10.2}%|", prob * 100.0);
+/// 将 `--fish` 解析为物品 Id，无法识别时退出程序。
+fn resolve_fish_or_exit(fish_query: &str, game_data: &stardew_fishing_prob::GameData) -> String {
+    match search::resolve_fish_id(fish_query, game_data) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("\n错误：{}", e);
+            std::process::exit(1);
         }
-        println!();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,334 @@
+//! src/minigame.rs
+//!
+//! 钓鱼小游戏的蒙特卡洛模拟，用于估算咬钩之后成功钓起的概率。
+//!
+//! 鱼的运动、钓鱼条的物理以及进度条的增减逐帧（60 帧/秒）复刻游戏中 BobberBar.update 的逻辑；
+//! 玩家由 `MinigameModel` 中的简单控制策略代替。
+//! 进度条从 0.3 开始，达到 1 即成功，降到 0 即失败。
+//! 影响结果的钓具：软木浮标加长钓鱼条，陷阱浮标减慢进度流失，
+//! 倒刺钩让钓鱼条在鱼身上更“粘”，铅制浮标让钓鱼条触底后不再反弹。
+//! 使用挑战鱼饵时，鱼每离开钓鱼条一次就少钓起一条，因此还统计了鱼离开钓鱼条的次数。
+
+use crate::models::{AppConfig, Bait, GameData, MinigameModel, MotionType, ProbabilityDetails, Tackle};
+use crate::utils;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+
+/// 小游戏区域的高度（像素），与游戏一致。
+const BAR_AREA_HEIGHT: f32 = 568.0;
+/// 鱼可以移动的最大位置。
+const FISH_MAX_POSITION: f32 = 532.0;
+const FRAMES_PER_SECOND: f64 = 60.0;
+
+/// 一次小游戏模拟所需的全部参数。
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinigameSetup {
+    pub difficulty: f32,
+    pub motion_type: MotionType,
+    pub fishing_level: u32,
+    pub is_training_rod: bool,
+    pub cork_bobbers: u32,
+    pub trap_bobbers: u32,
+    pub barbed_hooks: u32,
+    pub lead_bobbers: u32,
+}
+
+impl MinigameSetup {
+    /// 由鱼的数据与用户配置构建模拟参数。
+    pub fn new(difficulty: u32, motion_type: MotionType, config: &AppConfig) -> Self {
+        MinigameSetup {
+            difficulty: difficulty as f32,
+            motion_type,
+            fishing_level: config.fishing_level,
+            is_training_rod: config.is_training_rod(),
+            cork_bobbers: config.tackle_count(Tackle::CorkBobber),
+            trap_bobbers: config.tackle_count(Tackle::TrapBobber),
+            barbed_hooks: config.tackle_count(Tackle::BarbedHook),
+            lead_bobbers: config.tackle_count(Tackle::LeadBobber),
+        }
+    }
+
+    /// 钓鱼条的高度（像素）。
+    pub fn bar_height(&self) -> f32 {
+        let mut height = 96.0 + self.fishing_level as f32 * 8.0;
+        if self.is_training_rod && self.fishing_level < 5 {
+            height += 40.0 - self.fishing_level as f32 * 8.0;
+        }
+        height + 24.0 * self.cork_bobbers as f32
+    }
+}
+
+/// 蒙特卡洛模拟的结果。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinigameOutcome {
+    pub success_rate: f64,
+    /// 成功的局中，鱼从未离开钓鱼条（完美捕获）的比例
+    pub perfect_rate: f64,
+    /// 每局小游戏的平均时长（秒），包括失败的局
+    pub mean_seconds: f64,
+    /// 使用挑战鱼饵时，成功的局中平均钓起的数量：3 减去鱼离开钓鱼条的次数，至少为 1
+    pub challenge_bait_count: f64,
+}
+
+/// 运行 `model.trials` 局模拟。相同的参数与种子总是得到相同的结果。
+pub fn simulate(setup: &MinigameSetup, model: &MinigameModel, seed: u64) -> MinigameOutcome {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let max_frames = (model.max_seconds * FRAMES_PER_SECOND) as u32;
+    let trials = model.trials.max(1);
+
+    let mut successes = 0;
+    let mut perfects = 0;
+    let mut total_frames = 0u64;
+    let mut challenge_bait_fish = 0;
+    for _ in 0..trials {
+        let (success, perfect, frames, escapes) = simulate_once(setup, model, max_frames, &mut rng);
+        if success {
+            successes += 1;
+            challenge_bait_fish += 3u32.saturating_sub(escapes).max(1);
+        }
+        if success && perfect { perfects += 1; }
+        total_frames += frames as u64;
+    }
+
+    MinigameOutcome {
+        success_rate: successes as f64 / trials as f64,
+        perfect_rate: if successes > 0 { perfects as f64 / successes as f64 } else { 0.0 },
+        mean_seconds: total_frames as f64 / trials as f64 / FRAMES_PER_SECOND,
+        challenge_bait_count: if successes > 0 { challenge_bait_fish as f64 / successes as f64 } else { 1.0 },
+    }
+}
+
+/// 模拟一局小游戏，返回是否成功、是否完美、持续的帧数以及鱼离开钓鱼条的次数。
+fn simulate_once(setup: &MinigameSetup, model: &MinigameModel, max_frames: u32, rng: &mut StdRng) -> (bool, bool, u32, u32) {
+    let difficulty = setup.difficulty;
+    let bar_height = setup.bar_height();
+
+    let mut fish_position: f32 = 508.0;
+    let mut fish_target: f32 = (100.0 - difficulty) / 100.0 * 548.0;
+    let mut fish_speed: f32 = 0.0;
+    let mut floater_sinker_acceleration: f32 = 0.0;
+
+    let mut bar_position = BAR_AREA_HEIGHT - bar_height;
+    let mut bar_speed: f32 = 0.0;
+    let mut progress: f32 = 0.3;
+    let mut perfect = true;
+    let mut was_in_bar = true;
+    let mut escapes = 0;
+
+    // 玩家看到的是若干帧之前的鱼的位置
+    let mut seen_positions = VecDeque::from(vec![fish_position; model.reaction_frames as usize + 1]);
+
+    let escape_rate = (0.003 - 0.001 * (setup.trap_bobbers + setup.is_training_rod as u32) as f32).max(0.001);
+    let in_bar_gravity_factor = if setup.barbed_hooks > 0 { 0.3 } else { 0.6 };
+
+    for frame in 1..=max_frames {
+        // --- 鱼的运动 ---
+        let is_smooth = setup.motion_type == MotionType::Smooth;
+        let move_chance = difficulty * if is_smooth { 20.0 } else { 1.0 } / 4000.0;
+        if rng.gen::<f32>() < move_chance && (!is_smooth || fish_target == -1.0) {
+            let space_below = 548.0 - fish_position;
+            let space_above = fish_position;
+            let percent = (difficulty + rng.gen_range(10..45) as f32).min(99.0) / 100.0;
+            let low = (-space_above).min(space_below) as i32;
+            let high = space_below as i32;
+            let offset = if low < high { rng.gen_range(low..high) } else { low };
+            fish_target = fish_position + offset as f32 * percent;
+        }
+
+        match setup.motion_type {
+            MotionType::Floater => floater_sinker_acceleration = (floater_sinker_acceleration - 0.01).max(-1.5),
+            MotionType::Sinker => floater_sinker_acceleration = (floater_sinker_acceleration + 0.01).min(1.5),
+            _ => {}
+        }
+
+        if (fish_position - fish_target).abs() > 3.0 && fish_target != -1.0 {
+            let acceleration = (fish_target - fish_position)
+                / (rng.gen_range(10..30) as f32 + (100.0 - difficulty.min(100.0)));
+            fish_speed += (acceleration - fish_speed) / 5.0;
+        } else if !is_smooth && rng.gen::<f32>() < difficulty / 2000.0 {
+            fish_target = fish_position + random_jump(rng, 0);
+        } else {
+            fish_target = -1.0;
+        }
+
+        if setup.motion_type == MotionType::Dart && rng.gen::<f32>() < difficulty / 1000.0 {
+            fish_target = fish_position + random_jump(rng, difficulty as i32 * 2);
+        }
+
+        fish_target = fish_target.clamp(-1.0, 548.0);
+        fish_position = (fish_position + fish_speed + floater_sinker_acceleration).clamp(0.0, FISH_MAX_POSITION);
+
+        // --- 玩家与钓鱼条 ---
+        let bar_top = bar_position - 32.0;
+        let mut fish_in_bar = fish_position + 12.0 <= bar_top + bar_height && fish_position - 16.0 >= bar_top;
+        if fish_position >= 548.0 - bar_height && bar_position >= BAR_AREA_HEIGHT - bar_height - 4.0 {
+            fish_in_bar = true;
+        }
+
+        seen_positions.push_back(fish_position);
+        let seen_fish = seen_positions.pop_front().unwrap_or(fish_position);
+        // 让鱼处在钓鱼条判定区间的中央
+        let desired_bar_position = seen_fish + 30.0 - bar_height / 2.0;
+        let predicted_bar_position = bar_position + bar_speed * model.lookahead_frames as f32;
+        let button_pressed = predicted_bar_position > desired_bar_position;
+
+        let mut gravity: f32 = if button_pressed { -0.25 } else { 0.25 };
+        if button_pressed && (bar_position == 0.0 || bar_position == BAR_AREA_HEIGHT - bar_height) {
+            bar_speed = 0.0;
+        }
+        if fish_in_bar { gravity *= in_bar_gravity_factor; }
+
+        bar_speed += gravity;
+        bar_position += bar_speed;
+        if bar_position + bar_height > BAR_AREA_HEIGHT {
+            bar_position = BAR_AREA_HEIGHT - bar_height;
+            let bounce = if setup.lead_bobbers > 0 { 0.0 } else { 2.0 / 3.0 };
+            bar_speed = -bar_speed * bounce;
+        } else if bar_position < 0.0 {
+            bar_position = 0.0;
+            bar_speed = -bar_speed * 2.0 / 3.0;
+        }
+
+        // --- 进度 ---
+        if fish_in_bar {
+            progress += 0.002;
+        } else {
+            progress -= escape_rate;
+            perfect = false;
+            if was_in_bar { escapes += 1; }
+        }
+        was_in_bar = fish_in_bar;
+        if progress >= 1.0 { return (true, perfect, frame, escapes); }
+        if progress <= 0.0 { return (false, false, frame, escapes); }
+    }
+
+    (false, false, max_frames, escapes)
+}
+
+/// 鱼的随机跳跃：向上 [-100 - extra, -51] 或向下 [50, 100 + extra] 像素。
+fn random_jump(rng: &mut StdRng, extra: i32) -> f32 {
+    if rng.gen::<bool>() {
+        rng.gen_range(-100 - extra..-50) as f32
+    } else {
+        rng.gen_range(50..101 + extra) as f32
+    }
+}
+
+/// 按物品缓存模拟结果，避免在多个场景、时间段中重复模拟同一条鱼。
+/// 同一物品在不同的钓鱼等级、钓竿、钓具或小游戏模型下分别缓存，因此可以在不同的配置之间共用。
+#[derive(Debug, Default)]
+pub struct MinigameEstimator {
+    cache: HashMap<String, Vec<(MinigameSetup, MinigameModel, MinigameOutcome)>>,
+}
+
+impl MinigameEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 该物品的小游戏模拟结果。没有小游戏的物品（垃圾、藻类等）返回 `None`。
+    pub fn outcome(&mut self, display_id: &str, config: &AppConfig, game_data: &GameData) -> Option<MinigameOutcome> {
+        let fish = game_data.fish.get(display_id).filter(|fish| !fish.is_algae())?;
+        let setup = MinigameSetup::new(fish.difficulty, fish.motion_type, config);
+        Some(self.cached_outcome(display_id, setup, &config.minigame))
+    }
+
+    /// 查找或运行一次模拟。种子由物品与模型的种子决定，与缓存顺序无关。
+    fn cached_outcome(&mut self, display_id: &str, setup: MinigameSetup, model: &MinigameModel) -> MinigameOutcome {
+        let entries = self.cache.entry(display_id.to_string()).or_default();
+        if let Some((_, _, outcome)) = entries.iter().find(|(s, m, _)| *s == setup && m == model) {
+            return *outcome;
+        }
+
+        let outcome = simulate(&setup, model, item_seed(display_id, model.seed));
+        entries.push((setup, model.clone(), outcome));
+        outcome
+    }
+
+    /// 咬钩后成功钓起该物品的概率。没有小游戏的物品总是为 1。
+    pub fn catch_success_rate(&mut self, display_id: &str, config: &AppConfig, game_data: &GameData) -> f64 {
+        self.outcome(display_id, config, game_data).map_or(1.0, |outcome| outcome.success_rate)
+    }
+
+    /// 为一组概率结果填入 catch_success_prob 与 landed_prob；
+    /// 使用挑战鱼饵时，再用模拟得到的数量代替 expected_count 的估算值。
+    pub fn apply(&mut self, probabilities: &mut [ProbabilityDetails], config: &AppConfig, game_data: &GameData) {
+        let using_challenge_bait = config.bait() == Some(Bait::Challenge);
+        for p in probabilities {
+            let outcome = self.outcome(&p.display_id, config, game_data);
+            let success = outcome.map_or(1.0, |outcome| outcome.success_rate);
+            p.catch_success_prob = Some(success);
+            p.landed_prob = Some(p.final_prob * success);
+            if let Some(outcome) = outcome.filter(|_| using_challenge_bait) {
+                p.expected_count = outcome.challenge_bait_count;
+            }
+        }
+    }
+}
+
+/// 由物品 Id 与模型的种子派生该物品的模拟种子，不随 Rust 版本变化。
+fn item_seed(display_id: &str, seed: u64) -> u64 {
+    utils::splitmix64(utils::stable_hash(display_id) ^ utils::splitmix64(seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(difficulty: f32, motion_type: MotionType) -> MinigameSetup {
+        MinigameSetup {
+            difficulty,
+            motion_type,
+            fishing_level: 10,
+            is_training_rod: false,
+            cork_bobbers: 0,
+            trap_bobbers: 0,
+            barbed_hooks: 0,
+            lead_bobbers: 0,
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_outcome() {
+        let model = MinigameModel { trials: 200, ..MinigameModel::default() };
+        let fish = setup(85.0, MotionType::Mixed);
+        assert_eq!(simulate(&fish, &model, 42), simulate(&fish, &model, 42));
+    }
+
+    #[test]
+    fn item_seeds_are_fixed() {
+        assert_eq!(item_seed("(O)128", 1), 0x4C2F_426E_5ADB_C93E);
+        assert_ne!(item_seed("(O)128", 1), item_seed("(O)128", 2));
+        assert_ne!(item_seed("(O)128", 1), item_seed("(O)129", 1));
+    }
+
+    #[test]
+    fn estimator_cache_distinguishes_setups() {
+        let model = MinigameModel { trials: 200, ..MinigameModel::default() };
+        let low_level = MinigameSetup { fishing_level: 0, ..setup(85.0, MotionType::Mixed) };
+        let cork = MinigameSetup { cork_bobbers: 1, ..setup(85.0, MotionType::Mixed) };
+
+        let mut estimator = MinigameEstimator::new();
+        let first = estimator.cached_outcome("(O)128", setup(85.0, MotionType::Mixed), &model);
+        let low = estimator.cached_outcome("(O)128", low_level.clone(), &model);
+        let with_cork = estimator.cached_outcome("(O)128", cork.clone(), &model);
+        assert_ne!(first, low);
+        assert_ne!(first, with_cork);
+
+        // 命中缓存时与单独的估算器结果一致
+        let fresh = MinigameEstimator::new().cached_outcome("(O)128", low_level.clone(), &model);
+        assert_eq!(estimator.cached_outcome("(O)128", low_level, &model), fresh);
+        assert_eq!(estimator.cached_outcome("(O)128", setup(85.0, MotionType::Mixed), &model), first);
+        assert_eq!(estimator.cache["(O)128"].len(), 3);
+    }
+
+    #[test]
+    fn harder_fish_are_caught_less_often() {
+        let model = MinigameModel { trials: 300, ..MinigameModel::default() };
+        let easy = simulate(&setup(15.0, MotionType::Mixed), &model, 7);
+        let hard = simulate(&setup(110.0, MotionType::Dart), &model, 7);
+        assert!(easy.success_rate > 0.95, "easy: {:?}", easy);
+        assert!(hard.success_rate < easy.success_rate, "easy: {:?}, hard: {:?}", easy, hard);
+    }
+}
@@ -2,11 +2,12 @@
 //!
 //! 定义了程序中所有核心的数据结构。
 
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::hash::{Hash, Hasher};
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct Rect {
     pub x: i32,
@@ -15,6 +16,13 @@ pub struct Rect {
     pub height: i32,
 }
 
+impl Rect {
+    /// 与游戏中 Rectangle.Contains 一致：左、上边界包含在内，右、下边界不包含。
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct FishAreaData {
@@ -27,6 +35,53 @@ pub struct FishAreaData {
 }
 
 
+/// 数量修正器的运算方式，对应游戏中的 QuantityModifier.ModificationType。
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ModificationType {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Set,
+}
+
+impl ModificationType {
+    pub fn apply(self, value: f64, amount: f64) -> f64 {
+        match self {
+            ModificationType::Add => value + amount,
+            ModificationType::Subtract => value - amount,
+            ModificationType::Multiply => value * amount,
+            ModificationType::Divide => value / amount,
+            ModificationType::Set => amount,
+        }
+    }
+}
+
+/// 多个数量修正器同时生效时的组合方式。
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuantityModifierMode {
+    /// 依次叠加所有生效的修正器
+    #[default]
+    Stack,
+    /// 分别作用于原值，取结果中的最小值
+    Minimum,
+    /// 分别作用于原值，取结果中的最大值
+    Maximum,
+}
+
+/// 代表 ChanceModifiers 中的一个数量修正器。
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct QuantityModifier {
+    pub id: Option<String>,
+    pub condition: Option<String>,
+    pub modification: ModificationType,
+    #[serde(default)]
+    pub amount: f64,
+    #[serde(default)]
+    pub random_amount: Option<Vec<f64>>,
+}
+
 /// 代表从 Locations.json 中 'Fish' 数组里的一个条目。
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "PascalCase")]
@@ -38,6 +93,10 @@ pub struct SpawnFishData {
     pub precedence: i32,
     pub chance: f64,
     #[serde(default)]
+    pub chance_modifiers: Option<Vec<QuantityModifier>>,
+    #[serde(default)]
+    pub chance_modifier_mode: QuantityModifierMode,
+    #[serde(default)]
     pub ignore_fish_data_requirements: bool,
     #[serde(default = "default_bait_multiplier")]
     pub specific_bait_multiplier: f64,
@@ -93,6 +152,10 @@ pub struct LocationData {
 pub struct ParsedFishData {
     pub name: String,
     pub difficulty: u32,
+    pub motion_type: MotionType,
+    /// 尺寸范围（英寸）
+    pub min_size: u32,
+    pub max_size: u32,
     pub time_windows: Vec<(u32, u32)>,
     pub seasons: Vec<String>,
     pub weather: String,
@@ -103,11 +166,373 @@ pub struct ParsedFishData {
     pub is_tutorial_fish: bool,
 }
 
+/// Fish.json 中鱼在钓鱼小游戏里的运动方式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MotionType {
+    Mixed,
+    Dart,
+    Smooth,
+    Sinker,
+    Floater,
+}
+
+impl MotionType {
+    /// 解析 Fish.json 中的运动方式字段，无法识别时与游戏一致按 mixed 处理。
+    pub fn parse(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "dart" => MotionType::Dart,
+            "smooth" => MotionType::Smooth,
+            "sinker" => MotionType::Sinker,
+            "floater" => MotionType::Floater,
+            _ => MotionType::Mixed,
+        }
+    }
+}
+
+/// 钓竿种类，决定鱼饵与钓具的槽位数。
+/// 训练钓竿不能装备鱼饵和钓具，只能钓到难度低于 50 的鱼（刷鱼数据可以用 CanUseTrainingRod 覆盖），
+/// 但鱼的咬钩概率乘以 1.1，钓到的鱼总是普通品质。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rod {
+    BambooPole,
+    TrainingRod,
+    FiberglassRod,
+    IridiumRod,
+    AdvancedIridiumRod,
+}
+
+impl Rod {
+    /// 由工具 Id 识别钓竿种类。
+    pub fn from_item_id(item_id: &str) -> Option<Self> {
+        match item_id {
+            "(T)BambooPole" => Some(Rod::BambooPole),
+            "(T)TrainingRod" => Some(Rod::TrainingRod),
+            "(T)FiberglassRod" => Some(Rod::FiberglassRod),
+            "(T)IridiumRod" => Some(Rod::IridiumRod),
+            "(T)AdvancedIridiumRod" => Some(Rod::AdvancedIridiumRod),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rod::BambooPole => "Bamboo Pole",
+            Rod::TrainingRod => "Training Rod",
+            Rod::FiberglassRod => "Fiberglass Rod",
+            Rod::IridiumRod => "Iridium Rod",
+            Rod::AdvancedIridiumRod => "Advanced Iridium Rod",
+        }
+    }
+
+    pub fn bait_slots(self) -> usize {
+        match self {
+            Rod::BambooPole | Rod::TrainingRod => 0,
+            Rod::FiberglassRod | Rod::IridiumRod | Rod::AdvancedIridiumRod => 1,
+        }
+    }
+
+    pub fn tackle_slots(self) -> usize {
+        match self {
+            Rod::IridiumRod => 1,
+            Rod::AdvancedIridiumRod => 2,
+            _ => 0,
+        }
+    }
+
+    pub fn is_training(self) -> bool {
+        self == Rod::TrainingRod
+    }
+
+    /// 这根钓竿能否钓到该鱼。`can_use_training_rod` 为刷鱼数据中的覆盖值。
+    pub fn can_hook(self, difficulty: u32, can_use_training_rod: Option<bool>) -> bool {
+        !self.is_training() || can_use_training_rod.unwrap_or(difficulty < 50)
+    }
+
+    /// 咬钩概率的倍率。
+    pub fn bite_chance_multiplier(self) -> f64 {
+        if self.is_training() { 1.1 } else { 1.0 }
+    }
+}
+
+/// 鱼饵种类。每种鱼饵对刷鱼循环、咬钩时间与钓获数量的影响不同：
+/// - 普通鱼饵：只缩短咬钩时间，刷鱼循环仍只进行一次；
+/// - 其余鱼饵都是“好鱼饵”，刷鱼循环进行两次；
+/// - 野生鱼饵：有 25% + 每日运气 / 2 的概率一次钓起两条鱼；
+/// - 豪华鱼饵：进一步缩短咬钩时间，并提高鱼的尺寸系数；
+/// - 挑战鱼饵：一次钓起三条鱼，鱼每离开钓鱼条一次就少一条（至少一条）；
+/// - 特制鱼饵：提高目标鱼的概率（见 SpawnFishData 的 specific_bait_multiplier）；
+/// - 磁铁：提高宝箱概率；
+/// - 魔法鱼饵：忽略季节、时间与天气的限制。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bait {
+    Regular,
+    Wild,
+    Deluxe,
+    Challenge,
+    Targeted,
+    Magnet,
+    Magic,
+}
+
+impl Bait {
+    /// 由物品 Id 识别鱼饵种类。
+    pub fn from_item_id(item_id: &str) -> Option<Self> {
+        match item_id {
+            "(O)685" => Some(Bait::Regular),
+            "(O)774" => Some(Bait::Wild),
+            "(O)DeluxeBait" => Some(Bait::Deluxe),
+            "(O)ChallengeBait" => Some(Bait::Challenge),
+            "(O)SpecificBait" => Some(Bait::Targeted),
+            "(O)703" => Some(Bait::Magnet),
+            "(O)908" => Some(Bait::Magic),
+            _ => None,
+        }
+    }
+
+    pub fn item_id(self) -> &'static str {
+        match self {
+            Bait::Regular => "(O)685",
+            Bait::Wild => "(O)774",
+            Bait::Deluxe => "(O)DeluxeBait",
+            Bait::Challenge => "(O)ChallengeBait",
+            Bait::Targeted => "(O)SpecificBait",
+            Bait::Magnet => "(O)703",
+            Bait::Magic => "(O)908",
+        }
+    }
+
+    /// 英文名称，与游戏中的物品名一致（特制鱼饵除外）。
+    pub fn name(self) -> &'static str {
+        match self {
+            Bait::Regular => "Bait",
+            Bait::Wild => "Wild Bait",
+            Bait::Deluxe => "Deluxe Bait",
+            Bait::Challenge => "Challenge Bait",
+            Bait::Targeted => "Specific Bait",
+            Bait::Magnet => "Magnet",
+            Bait::Magic => "Magic Bait",
+        }
+    }
+
+    /// 刷鱼循环的次数：普通鱼饵 1 次，其余鱼饵 2 次。
+    pub fn spawn_passes(self) -> i32 {
+        if self == Bait::Regular { 1 } else { 2 }
+    }
+
+    /// 是否忽略季节、时间与天气的限制。
+    pub fn ignores_conditions(self) -> bool {
+        self == Bait::Magic
+    }
+
+    /// 在所有鱼饵共有的咬钩时间倍率之上，额外乘以的倍率。
+    pub fn bite_wait_factor(self) -> f64 {
+        if self == Bait::Deluxe { 0.66 } else { 1.0 }
+    }
+
+    /// 尺寸系数的倍率。
+    pub fn size_multiplier(self) -> f64 {
+        if self == Bait::Deluxe { 1.2 } else { 1.0 }
+    }
+
+    /// 宝箱概率的加成。
+    pub fn treasure_chance_bonus(self) -> f64 {
+        if self == Bait::Magnet { 0.15 } else { 0.0 }
+    }
+
+    /// 成功钓起一条有小游戏的鱼时，期望得到的数量。
+    /// `perfect_rate` 只用于挑战鱼饵：完美捕获得到 3 条，否则保守地按 1 条估算；
+    /// 启用小游戏模拟时由模拟得到的逃脱次数代替。
+    pub fn expected_fish_count(self, daily_luck: f64, perfect_rate: f64) -> f64 {
+        match self {
+            Bait::Wild => 1.0 + (0.25 + daily_luck / 2.0).clamp(0.0, 1.0),
+            Bait::Challenge => 3.0 * perfect_rate + (1.0 - perfect_rate),
+            _ => 1.0,
+        }
+    }
+}
+
+/// 钓具种类。每种钓具只影响钓鱼过程中的一个环节：
+/// - 咬钩时间：旋转器、精致旋转器缩短等待咬钩的最长时间；
+/// - 刷鱼概率：珍稀诱钩提高稀有鱼的概率；
+/// - 小游戏：陷阱浮标、软木浮标、铅制浮标、倒刺钩（见 `minigame`）；
+/// - 宝箱：寻宝者提高宝箱概率；
+/// - 品质：品质浮标提升一级品质；
+/// - 声呐浮标只显示上钩的鱼，不影响任何概率。
+///
+/// 铱金钓竿与高级铱金钓竿可以装备钓具，同一种钓具装备两个时效果叠加。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tackle {
+    Spinner,
+    DressedSpinner,
+    TrapBobber,
+    CorkBobber,
+    LeadBobber,
+    TreasureHunter,
+    BarbedHook,
+    CuriosityLure,
+    QualityBobber,
+    SonarBobber,
+}
+
+impl Tackle {
+    /// 由物品 Id 识别钓具种类。
+    pub fn from_item_id(item_id: &str) -> Option<Self> {
+        match item_id {
+            "(O)686" => Some(Tackle::Spinner),
+            "(O)687" => Some(Tackle::DressedSpinner),
+            "(O)694" => Some(Tackle::TrapBobber),
+            "(O)695" => Some(Tackle::CorkBobber),
+            "(O)692" => Some(Tackle::LeadBobber),
+            "(O)693" => Some(Tackle::TreasureHunter),
+            "(O)691" => Some(Tackle::BarbedHook),
+            "(O)856" => Some(Tackle::CuriosityLure),
+            "(O)877" => Some(Tackle::QualityBobber),
+            "(O)SonarBobber" => Some(Tackle::SonarBobber),
+            _ => None,
+        }
+    }
+
+    pub fn item_id(self) -> &'static str {
+        match self {
+            Tackle::Spinner => "(O)686",
+            Tackle::DressedSpinner => "(O)687",
+            Tackle::TrapBobber => "(O)694",
+            Tackle::CorkBobber => "(O)695",
+            Tackle::LeadBobber => "(O)692",
+            Tackle::TreasureHunter => "(O)693",
+            Tackle::BarbedHook => "(O)691",
+            Tackle::CuriosityLure => "(O)856",
+            Tackle::QualityBobber => "(O)877",
+            Tackle::SonarBobber => "(O)SonarBobber",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tackle::Spinner => "Spinner",
+            Tackle::DressedSpinner => "Dressed Spinner",
+            Tackle::TrapBobber => "Trap Bobber",
+            Tackle::CorkBobber => "Cork Bobber",
+            Tackle::LeadBobber => "Lead Bobber",
+            Tackle::TreasureHunter => "Treasure Hunter",
+            Tackle::BarbedHook => "Barbed Hook",
+            Tackle::CuriosityLure => "Curiosity Lure",
+            Tackle::QualityBobber => "Quality Bobber",
+            Tackle::SonarBobber => "Sonar Bobber",
+        }
+    }
+
+    /// 等待咬钩的最长时间减少的秒数。
+    pub fn bite_wait_reduction_seconds(self) -> f64 {
+        match self {
+            Tackle::Spinner => 5.0,
+            Tackle::DressedSpinner => 10.0,
+            _ => 0.0,
+        }
+    }
+
+    /// 宝箱概率的加成。
+    pub fn treasure_chance_bonus(self) -> f64 {
+        if self == Tackle::TreasureHunter { 0.05 } else { 0.0 }
+    }
+}
+
+impl ParsedFishData {
+    /// 藻类（绿藻、白藻、海草）没有钓鱼小游戏，也无法制作特制鱼饵。
+    pub fn is_algae(&self) -> bool {
+        matches!(self.name.as_str(), "Green Algae" | "White Algae" | "Seaweed")
+    }
+}
+
+/// Objects.json（与游戏的 Data/Objects 格式相同）中用到的字段。
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ObjectData {
+    pub name: String,
+    /// 物品类别，鱼为 -4
+    #[serde(default)]
+    pub category: i32,
+    #[serde(default)]
+    pub price: u32,
+}
+
+impl ObjectData {
+    pub fn is_fish(&self) -> bool {
+        self.category == -4
+    }
+}
+
+/// 代表解析后的 Fish.json 中 trap 类型（蟹笼）的条目。
+#[derive(Debug, Clone)]
+pub struct ParsedCrabPotData {
+    pub item_id: String,
+    pub name: String,
+    pub chance: f64,
+    /// 可捕获的水域类型，例如 "ocean"、"freshwater"
+    pub water_types: Vec<String>,
+}
+
 /// 一个聚合所有游戏数据的容器，便于在函数间传递。
 pub struct GameData {
     pub locations: HashMap<String, LocationData>,
     pub fish: HashMap<String, ParsedFishData>,
     pub fish_name_to_id: HashMap<String, String>,
+    /// 按 Fish.json 中的顺序排列，蟹笼按此顺序依次判定
+    pub crab_pot_fish: Vec<ParsedCrabPotData>,
+    /// Objects.json 中的物品数据，键为带 "(O)" 前缀的物品ID；文件不存在时为空
+    pub objects: HashMap<String, ObjectData>,
+    /// TreasureLoot.json 中的宝箱战利品表；文件不存在时为 `None`
+    pub treasure_loot: Option<TreasureLootData>,
+    pub string_map: StringMap,
+}
+
+/// 宝箱的战利品表 (TreasureLoot.json)。
+/// 开宝箱时先必定抽取一次，之后每抽一次，继续抽取的概率就乘以 `roll_chance`（金色宝箱为 `golden_roll_chance`）。
+/// 每次抽取等概率地选择一个类别，再在类别中满足水深与钓鱼等级要求的物品里按权重抽取。
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct TreasureLootData {
+    pub roll_chance: f64,
+    pub golden_roll_chance: f64,
+    pub categories: Vec<TreasureCategory>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct TreasureCategory {
+    pub id: String,
+    pub items: Vec<TreasureLootEntry>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct TreasureLootEntry {
+    pub item_id: String,
+    pub name: String,
+    pub weight: f64,
+    pub min_stack: u32,
+    pub max_stack: u32,
+    #[serde(default)]
+    pub min_water_depth: u32,
+    #[serde(default)]
+    pub min_fishing_level: u32,
+}
+
+/// 刷鱼条目的来源：条目所在的地点，以及它在该地点 Fish 列表中的下标。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpawnSource<'a> {
+    pub location: &'a str,
+    pub index: usize,
+}
+
+impl fmt::Display for SpawnSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.location, self.index)
+    }
 }
 
 /// 一个被完全解析后的可捕获物品。
@@ -115,23 +540,107 @@ pub struct GameData {
 pub struct ResolvedItem<'a> {
     pub display_id: String,
     pub source_data: &'a SpawnFishData,
+    /// source_data 的来源；经由 LOCATION_FISH 继承的物品记录的是被继承地点中的条目
+    pub source: SpawnSource<'a>,
+    /// Condition 成立的概率（含 LOCATION_FISH 父条目的条件），会乘入 GetChance
+    pub condition_weight: f64,
+    /// 本条目及其 LOCATION_FISH 父条目的 Condition，用于在各时间段内重新求值
+    pub conditions: Vec<&'a str>,
 }
 
 impl<'a> PartialEq for ResolvedItem<'a> {
     fn eq(&self, other: &Self) -> bool {
-        self.display_id == other.display_id 
-        && std::ptr::eq(self.source_data, other.source_data)
+        self.display_id == other.display_id && self.source == other.source
     }
 }
 impl<'a> Eq for ResolvedItem<'a> {}
 impl<'a> Hash for ResolvedItem<'a> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.display_id.hash(state);
-        (self.source_data as *const SpawnFishData).hash(state);
+        self.source.hash(state);
+    }
+}
+
+/// 刷鱼条目被过滤掉的原因。大部分对应 `resolve_fish_list` 中的各项静态检查，
+/// 其余来自咬钩阶段（`calculator::bite_exclusion`）与时间段过滤（`calculator::segment_exclusion`）。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExclusionReason {
+    /// 经由 LOCATION_FISH 继承的 Default 条目，且 CanBeInherited 为 false
+    NotInheritable,
+    /// 玩家或浮标不在 PlayerPosition / BobberPosition 范围内
+    PositionRestricted,
+    FishingLevelTooLow { required: u32, current: u32 },
+    TooShallow { min_distance: u32, water_depth: u32 },
+    TooDeep { max_distance: u32, water_depth: u32 },
+    RequiresMagicBait,
+    /// CatchLimit 为 1 且已经钓到过
+    CatchLimitReached,
+    /// Condition 不成立，`queries` 为其中求值为 0 的子条件
+    ConditionFailed { queries: Vec<String> },
+    OutOfSeason { seasons: String, current: String },
+    WrongWeather { required: String, current: String },
+    /// SECRET_NOTE_OR_ITEM 在已收集全部秘密纸条时不再出现
+    AllSecretNotesFound,
+    /// 咬钩阶段：训练钓竿钓不到该鱼（难度不低于 50，或 CanUseTrainingRod 为 false）
+    TrainingRodCannotHook { difficulty: u32 },
+    /// 咬钩阶段：教程钓鱼只能钓到教程鱼
+    NotTutorialFish,
+    /// 时间段阶段：Fish.json 的时间窗口与时间段没有重叠
+    OutsideFishTime { windows: Vec<(u32, u32)> },
+}
+
+impl fmt::Display for ExclusionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExclusionReason::NotInheritable => write!(f, "不能经由 LOCATION_FISH 继承"),
+            ExclusionReason::PositionRestricted => write!(f, "玩家或浮标不在指定范围内"),
+            ExclusionReason::FishingLevelTooLow { required, current } => {
+                write!(f, "需要钓鱼等级 {}，当前为 {}", required, current)
+            }
+            ExclusionReason::TooShallow { min_distance, water_depth } => {
+                write!(f, "需要水深至少 {}，当前为 {}", min_distance, water_depth)
+            }
+            ExclusionReason::TooDeep { max_distance, water_depth } => {
+                write!(f, "需要水深至多 {}，当前为 {}", max_distance, water_depth)
+            }
+            ExclusionReason::RequiresMagicBait => write!(f, "需要魔法鱼饵"),
+            ExclusionReason::CatchLimitReached => write!(f, "只能钓到一次，已经钓到过"),
+            ExclusionReason::ConditionFailed { queries } if queries.is_empty() => write!(f, "条件不满足"),
+            ExclusionReason::ConditionFailed { queries } => write!(f, "条件不满足：{}", queries.join("，")),
+            ExclusionReason::OutOfSeason { seasons, current } => {
+                write!(f, "只在 {} 出现，当前为 {}", seasons, current)
+            }
+            ExclusionReason::WrongWeather { required, current } => {
+                write!(f, "只在 {} 天气出现，当前为 {}", required, current)
+            }
+            ExclusionReason::AllSecretNotesFound => write!(f, "已收集全部秘密纸条"),
+            ExclusionReason::TrainingRodCannotHook { difficulty } => write!(f, "训练钓竿钓不到难度 {} 的鱼", difficulty),
+            ExclusionReason::NotTutorialFish => write!(f, "教程钓鱼只能钓到教程鱼"),
+            ExclusionReason::OutsideFishTime { windows } => {
+                let windows: Vec<String> = windows.iter().map(|(start, end)| format!("{} - {}", start, end)).collect();
+                write!(f, "只在 {} 出现", windows.join("，"))
+            }
+        }
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// 一个在解析阶段被过滤掉的刷鱼条目。
+#[derive(Debug, Clone)]
+pub struct ExcludedEntry<'a> {
+    pub source_data: &'a SpawnFishData,
+    pub source: SpawnSource<'a>,
+    pub reason: ExclusionReason,
+}
+
+/// 解析一个 FishArea 的结果：可能钓到的物品，以及被过滤掉的条目。
+#[derive(Debug, Clone, Default)]
+pub struct FishListResolution<'a> {
+    pub included: Vec<ResolvedItem<'a>>,
+    pub excluded: Vec<ExcludedEntry<'a>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct StringMap {
     pub seasons: HashMap<String, String>,
@@ -141,12 +650,137 @@ pub struct StringMap {
     pub tackle_internal_ids: HashMap<String, String>,
     #[serde(default)]
     pub fish_names: HashMap<String, String>,
+    #[serde(default)]
+    pub professions: HashMap<String, String>,
+}
+
+/// 供游戏状态查询 (GSQ) 使用的结构化游戏状态。
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default, rename_all = "snake_case")]
+pub struct GameState {
+    pub year: u32,
+    pub day_of_month: u32,
+    /// 当前时间（例如 1830），指定后只分析包含该时间的时间段。`None` 表示不针对具体时间求值
+    pub time_of_day: Option<u32>,
+    /// 游戏内部的天气ID（Sun、Rain、Storm、GreenRain、Snow、Wind 等）。
+    /// 未指定时由 sunny/rainy 推导为 Sun/Rain
+    pub weather_id: Option<String>,
+    pub mail_received: HashSet<String>,
+    /// 背包中的物品ID，可带或不带 "(O)" 等类型前缀
+    pub items: HashSet<String>,
+    pub special_order_rules: HashSet<String>,
+    pub passive_festivals: HashSet<String>,
+    pub is_festival_day: bool,
+    pub island_north_bridge_fixed: bool,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        GameState {
+            year: 1,
+            day_of_month: 1,
+            time_of_day: None,
+            weather_id: None,
+            mail_received: HashSet::new(),
+            items: HashSet::new(),
+            special_order_rules: HashSet::new(),
+            passive_festivals: HashSet::new(),
+            is_festival_day: false,
+            island_north_bridge_fixed: false,
+        }
+    }
+}
+
+impl GameState {
+    /// 返回用于 WEATHER 查询的天气ID。
+    pub fn weather_id<'a>(&'a self, weather: &str) -> &'a str {
+        match &self.weather_id {
+            Some(id) => id,
+            None if weather == "rainy" => "Rain",
+            None => "Sun",
+        }
+    }
+
+    /// 检查背包中是否有该物品，忽略 "(O)" 前缀的差异。
+    pub fn has_item(&self, item_id: &str) -> bool {
+        let unqualified = item_id.strip_prefix("(O)").unwrap_or(item_id);
+        self.items.contains(item_id)
+            || self.items.contains(unqualified)
+            || self.items.contains(&format!("(O){}", unqualified))
+    }
 }
 
 fn default_water_depth() -> u32 { 4 }
 
+/// 估算抛竿耗时所用的时间模型，单位均为现实秒。
+/// 默认值参照游戏：等待咬钩时间在 [最小值, 最大值 - 每级减少量 × 钓鱼等级] 中均匀分布，
+/// 使用鱼饵时减半；游戏内 10 分钟对应现实 7 秒。
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default, rename_all = "snake_case")]
+pub struct CastTimingModel {
+    /// 蓄力、抛出与收回鱼线的时间
+    pub cast_seconds: f64,
+    pub bite_wait_min_seconds: f64,
+    pub bite_wait_max_seconds: f64,
+    /// 每级钓鱼等级减少的最长等待时间
+    pub bite_wait_reduction_per_level: f64,
+    /// 使用鱼饵时等待时间的倍率
+    pub bait_bite_wait_multiplier: f64,
+    /// 钓到鱼时小游戏的平均时长
+    pub minigame_seconds: f64,
+    /// 钓到垃圾、藻类等没有小游戏的物品时的收线时长
+    pub reel_seconds: f64,
+    /// 游戏内 1 分钟对应的现实秒数
+    pub seconds_per_game_minute: f64,
+}
+
+impl Default for CastTimingModel {
+    fn default() -> Self {
+        CastTimingModel {
+            cast_seconds: 1.5,
+            bite_wait_min_seconds: 0.6,
+            bite_wait_max_seconds: 30.0,
+            bite_wait_reduction_per_level: 0.25,
+            bait_bite_wait_multiplier: 0.5,
+            minigame_seconds: 10.0,
+            reel_seconds: 2.0,
+            seconds_per_game_minute: 0.7,
+        }
+    }
+}
+
+/// 钓鱼小游戏蒙特卡洛模拟的参数。
+/// 模拟的玩家以 `reaction_frames` 帧之前看到的鱼的位置为目标，
+/// 并根据钓鱼条当前速度预测 `lookahead_frames` 帧之后的位置来决定是否按下按键。
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default, rename_all = "snake_case")]
+pub struct MinigameModel {
+    /// 是否计算“成功钓起”的概率
+    pub enabled: bool,
+    /// 每条鱼模拟的次数
+    pub trials: u32,
+    pub reaction_frames: u32,
+    pub lookahead_frames: u32,
+    /// 单次小游戏的最长时长（秒），超时视为失败
+    pub max_seconds: f64,
+    pub seed: u64,
+}
+
+impl Default for MinigameModel {
+    fn default() -> Self {
+        MinigameModel {
+            enabled: false,
+            trials: 1000,
+            reaction_frames: 8,
+            lookahead_frames: 12,
+            max_seconds: 120.0,
+            seed: 1,
+        }
+    }
+}
+
 /// 代表从 config.json 加载的原始用户输入。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct UserConfigRaw {
     #[serde(default)]
@@ -167,17 +801,110 @@ pub struct UserConfigRaw {
     pub conditions: HashMap<String, String>,
     #[serde(default)]
     pub fish_caught: Vec<(String, u32)>,
+    #[serde(default)]
+    pub professions: Vec<String>,
+    #[serde(default)]
+    pub game_state: GameState,
+    #[serde(default)]
+    pub cast_timing: CastTimingModel,
+    #[serde(default)]
+    pub minigame: MinigameModel,
+    /// 未启用小游戏模拟时，假设的完美捕获概率
+    #[serde(default)]
+    pub perfect_catch_rate: f64,
+    /// 是否已解锁钓鱼精通，解锁后宝箱有机会成为金色宝箱
+    #[serde(default)]
+    pub has_fishing_mastery: bool,
+    /// 玩家所站的图块 [x, y]。未指定时不按 PlayerPosition 过滤
+    #[serde(default)]
+    pub player_tile: Option<(i32, i32)>,
+    /// 浮标所在的图块 [x, y]。未指定时不按 BobberPosition 过滤
+    #[serde(default)]
+    pub bobber_tile: Option<(i32, i32)>,
+}
+
+/// 命令行提供的逐字段覆盖项，叠加在 UserConfigRaw 之上。
+/// `None` / 空列表表示不覆盖配置文件中的值。
+#[derive(Debug, Default, Clone)]
+pub struct ConfigOverrides {
+    pub is_tutorial_catch: Option<bool>,
+    pub location_name: Option<String>,
+    pub rod_type: Option<String>,
+    pub bait_type: Option<String>,
+    pub tackles: Option<Vec<String>>,
+    pub weather: Option<String>,
+    pub season: Option<String>,
+    pub fishing_level: Option<u32>,
+    pub luck_level: Option<u32>,
+    pub daily_luck: Option<f64>,
+    pub water_depth: Option<u32>,
+    pub professions: Option<Vec<String>>,
+    pub minigame_enabled: Option<bool>,
+    pub perfect_catch_rate: Option<f64>,
+    pub has_fishing_mastery: Option<bool>,
+    pub player_tile: Option<(i32, i32)>,
+    pub bobber_tile: Option<(i32, i32)>,
+    pub year: Option<u32>,
+    pub day_of_month: Option<u32>,
+    pub time_of_day: Option<u32>,
+    pub is_festival_day: Option<bool>,
+    pub island_north_bridge_fixed: Option<bool>,
+    /// 以下几项追加到 game_state 中对应的集合
+    pub mail_received: Vec<String>,
+    pub items: Vec<String>,
+    pub special_order_rules: Vec<String>,
+    pub passive_festivals: Vec<String>,
+    /// 逐项合并进 conditions，同名键会被覆盖
+    pub conditions: Vec<(String, String)>,
+    /// 追加到 fish_caught 之后
+    pub fish_caught: Vec<(String, u32)>,
+}
+
+impl ConfigOverrides {
+    /// 将覆盖项应用到从配置文件读取的原始配置上。
+    pub fn apply_to(&self, raw: &mut UserConfigRaw) {
+        if let Some(v) = self.is_tutorial_catch { raw.is_tutorial_catch = v; }
+        if let Some(v) = &self.location_name { raw.location_name = v.clone(); }
+        if let Some(v) = &self.rod_type { raw.rod_type = v.clone(); }
+        if let Some(v) = &self.bait_type { raw.bait_type = v.clone(); }
+        if let Some(v) = &self.tackles { raw.tackles = v.clone(); }
+        if let Some(v) = &self.weather { raw.weather = v.clone(); }
+        if let Some(v) = &self.season { raw.season = v.clone(); }
+        if let Some(v) = self.fishing_level { raw.fishing_level = v; }
+        if let Some(v) = self.luck_level { raw.luck_level = v; }
+        if let Some(v) = self.daily_luck { raw.daily_luck = v; }
+        if let Some(v) = self.water_depth { raw.water_depth = v; }
+        if let Some(v) = &self.professions { raw.professions = v.clone(); }
+        if let Some(v) = self.minigame_enabled { raw.minigame.enabled = v; }
+        if let Some(v) = self.perfect_catch_rate { raw.perfect_catch_rate = v; }
+        if let Some(v) = self.has_fishing_mastery { raw.has_fishing_mastery = v; }
+        if let Some(v) = self.player_tile { raw.player_tile = Some(v); }
+        if let Some(v) = self.bobber_tile { raw.bobber_tile = Some(v); }
+        let state = &mut raw.game_state;
+        if let Some(v) = self.year { state.year = v; }
+        if let Some(v) = self.day_of_month { state.day_of_month = v; }
+        if let Some(v) = self.time_of_day { state.time_of_day = Some(v); }
+        if let Some(v) = self.is_festival_day { state.is_festival_day = v; }
+        if let Some(v) = self.island_north_bridge_fixed { state.island_north_bridge_fixed = v; }
+        state.mail_received.extend(self.mail_received.iter().cloned());
+        state.items.extend(self.items.iter().cloned());
+        state.special_order_rules.extend(self.special_order_rules.iter().cloned());
+        state.passive_festivals.extend(self.passive_festivals.iter().cloned());
+        raw.conditions.extend(self.conditions.iter().cloned());
+        raw.fish_caught.extend(self.fish_caught.iter().cloned());
+    }
 }
 
 /// 解析后，供程序内部所有计算函数使用的最终配置。
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub is_tutorial_catch: bool,
-    pub is_training_rod: bool,
-    pub using_good_bait: bool,
+    pub rod: Rod,
+    /// 鱼饵的物品 Id，种类由 `bait()` 识别
     pub bait_item_id: Option<String>,
     pub bait_target_fish_id: Option<String>,
-    pub has_curiosity_lure: bool,
+    /// 已装备的钓具，最多两个
+    pub tackles: Vec<Tackle>,
     pub location_name: String,
     pub season: String,
     pub weather: String,
@@ -187,11 +914,95 @@ pub struct AppConfig {
     pub daily_luck: f64,
     pub conditions: HashMap<String, String>,
     pub fish_caught: HashMap<String, u32>,
+    /// 已选择的职业，使用内部名称，例如 "Mariner"、"Luremaster"
+    pub professions: HashSet<String>,
+    pub game_state: GameState,
+    pub cast_timing: CastTimingModel,
+    pub minigame: MinigameModel,
+    pub perfect_catch_rate: f64,
+    pub has_fishing_mastery: bool,
+    /// 玩家与浮标所在的图块，`None` 表示不按位置过滤
+    pub player_tile: Option<(i32, i32)>,
+    pub bobber_tile: Option<(i32, i32)>,
 }
 
-/// 用于在main函数中传递和打印最终详细概率信息的结构体。
-#[allow(dead_code)] 
-#[derive(Debug, Clone)]
+impl AppConfig {
+    /// 刷鱼条目的 PlayerPosition / BobberPosition 是否允许当前位置。未指定的位置视为满足。
+    pub fn position_allows(&self, spawn_data: &SpawnFishData) -> bool {
+        let allows = |rect: &Option<Rect>, tile: Option<(i32, i32)>| match (rect, tile) {
+            (Some(rect), Some(tile)) => rect.contains(tile),
+            _ => true,
+        };
+        allows(&spawn_data.player_position, self.player_tile) && allows(&spawn_data.bobber_position, self.bobber_tile)
+    }
+
+    /// 刷鱼条目带有位置限制，但配置中没有指定对应的图块，因此没有按位置过滤。
+    pub fn position_unchecked(&self, spawn_data: &SpawnFishData) -> bool {
+        (spawn_data.player_position.is_some() && self.player_tile.is_none())
+            || (spawn_data.bobber_position.is_some() && self.bobber_tile.is_none())
+    }
+
+    /// 当前使用的鱼饵种类。没有使用鱼饵时为 `None`。
+    pub fn bait(&self) -> Option<Bait> {
+        self.bait_item_id.as_deref().and_then(Bait::from_item_id)
+    }
+
+    /// 装备了几个该种钓具。
+    pub fn tackle_count(&self, tackle: Tackle) -> u32 {
+        self.tackles.iter().filter(|t| **t == tackle).count() as u32
+    }
+
+    pub fn has_curiosity_lure(&self) -> bool {
+        self.tackles.contains(&Tackle::CuriosityLure)
+    }
+
+    pub fn is_training_rod(&self) -> bool {
+        self.rod.is_training()
+    }
+
+    /// 装备或卸下珍稀诱钩。槽位已满时，珍稀诱钩替换最后一个钓具。
+    pub fn set_curiosity_lure(&mut self, equipped: bool) {
+        self.tackles.retain(|t| *t != Tackle::CuriosityLure);
+        if equipped {
+            self.tackles.truncate(self.rod.tackle_slots().saturating_sub(1));
+            self.tackles.push(Tackle::CuriosityLure);
+        }
+    }
+}
+
+#[cfg(test)]
+impl AppConfig {
+    /// 单元测试使用的配置：铱金钓竿、没有鱼饵与钓具、夏天晴天、钓鱼等级 10、水深 4。
+    pub(crate) fn for_test() -> Self {
+        AppConfig {
+            is_tutorial_catch: false,
+            rod: Rod::IridiumRod,
+            bait_item_id: None,
+            bait_target_fish_id: None,
+            tackles: vec![],
+            location_name: "Beach".to_string(),
+            season: "summer".to_string(),
+            weather: "sunny".to_string(),
+            water_depth: 4,
+            fishing_level: 10,
+            luck_level: 0,
+            daily_luck: 0.0,
+            conditions: HashMap::new(),
+            fish_caught: HashMap::new(),
+            professions: HashSet::new(),
+            game_state: GameState::default(),
+            cast_timing: CastTimingModel::default(),
+            minigame: MinigameModel::default(),
+            perfect_catch_rate: 0.0,
+            has_fishing_mastery: false,
+            player_tile: None,
+            bobber_tile: None,
+        }
+    }
+}
+
+/// 单个物品的最终详细概率信息。
+#[derive(Debug, Clone, Serialize)]
 pub struct ProbabilityDetails {
     pub display_id: String,
     pub name: String,
@@ -200,4 +1011,101 @@ pub struct ProbabilityDetails {
     pub bite_chance_prob: f64,
     pub final_prob: f64,
     pub source_group_id: String,
-}
\ No newline at end of file
+    /// 刷鱼条目的来源，格式为 `地点#下标`，例如 `Default#3`
+    pub source: String,
+    /// 咬钩后成功完成小游戏的概率，仅在启用小游戏模拟时计算
+    pub catch_success_prob: Option<f64>,
+    /// 一次抛竿最终钓起该物品的概率 (final_prob × catch_success_prob)
+    pub landed_prob: Option<f64>,
+    /// 每次钓起时期望得到的数量，野生鱼饵与挑战鱼饵会大于 1
+    pub expected_count: f64,
+    /// 钓起后的尺寸与品质分布，仅对有小游戏的鱼计算
+    pub quality: Option<FishQuality>,
+}
+
+/// 一条鱼钓起后的尺寸与品质分布。
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FishQuality {
+    pub normal: f64,
+    pub silver: f64,
+    pub gold: f64,
+    pub iridium: f64,
+    /// 期望尺寸（英寸）
+    pub mean_size: f64,
+    /// (尺寸, 概率)，按尺寸从小到大排列
+    pub size_distribution: Vec<(u32, f64)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_BAITS: [Bait; 7] =
+        [Bait::Regular, Bait::Wild, Bait::Deluxe, Bait::Challenge, Bait::Targeted, Bait::Magnet, Bait::Magic];
+
+    #[test]
+    fn bait_effects() {
+        for bait in ALL_BAITS {
+            assert_eq!(Bait::from_item_id(bait.item_id()), Some(bait));
+            assert_eq!(bait.spawn_passes(), if bait == Bait::Regular { 1 } else { 2 });
+            assert_eq!(bait.bite_wait_factor(), if bait == Bait::Deluxe { 0.66 } else { 1.0 });
+            assert_eq!(bait.size_multiplier(), if bait == Bait::Deluxe { 1.2 } else { 1.0 });
+            assert_eq!(bait.treasure_chance_bonus(), if bait == Bait::Magnet { 0.15 } else { 0.0 });
+        }
+    }
+
+    #[test]
+    fn expected_fish_count_for_wild_and_challenge_bait() {
+        // 野生鱼饵：1 + clamp(0.25 + 每日运气 / 2, 0, 1)
+        assert_eq!(Bait::Wild.expected_fish_count(0.0, 0.0), 1.25);
+        assert_eq!(Bait::Wild.expected_fish_count(0.1, 0.0), 1.3);
+        assert_eq!(Bait::Wild.expected_fish_count(-0.6, 0.0), 1.0);
+        assert_eq!(Bait::Wild.expected_fish_count(2.0, 0.0), 2.0);
+
+        // 挑战鱼饵：3p + (1 - p)
+        assert_eq!(Bait::Challenge.expected_fish_count(0.0, 0.0), 1.0);
+        assert_eq!(Bait::Challenge.expected_fish_count(0.0, 0.5), 2.0);
+        assert_eq!(Bait::Challenge.expected_fish_count(0.1, 1.0), 3.0);
+
+        for bait in ALL_BAITS.into_iter().filter(|b| !matches!(b, Bait::Wild | Bait::Challenge)) {
+            assert_eq!(bait.expected_fish_count(0.1, 1.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn rect_excludes_right_and_bottom_edges() {
+        let rect = Rect { x: 10, y: 20, width: 3, height: 2 };
+        assert!(rect.contains((10, 20)));
+        assert!(rect.contains((12, 21)));
+        assert!(!rect.contains((13, 20)));
+        assert!(!rect.contains((10, 22)));
+        assert!(!rect.contains((9, 20)));
+        assert!(!rect.contains((10, 19)));
+    }
+
+    #[test]
+    fn position_restrictions_apply_only_to_configured_tiles() {
+        // Town 中鮟鱇鱼 (O)160 的条目：玩家需要站在地图最北侧
+        let spawn_data: SpawnFishData = serde_json::from_str(r#"{
+            "Id": "(O)160", "ItemId": "(O)160", "Precedence": 0, "Chance": 1.0, "Condition": null, "Season": null,
+            "FishAreaId": null, "BobberPosition": null, "PlayerPosition": { "X": 0, "Y": 0, "Width": 255, "Height": 15 },
+            "CanUseTrainingRod": null, "SetFlagOnCatch": null
+        }"#).expect("spawn entry parses");
+
+        let config = AppConfig::for_test();
+        assert!(config.position_allows(&spawn_data));
+        assert!(config.position_unchecked(&spawn_data));
+
+        let north = AppConfig { player_tile: Some((50, 14)), ..AppConfig::for_test() };
+        assert!(north.position_allows(&spawn_data));
+        assert!(!north.position_unchecked(&spawn_data));
+
+        let south = AppConfig { player_tile: Some((50, 15)), ..AppConfig::for_test() };
+        assert!(!south.position_allows(&spawn_data));
+
+        // 只指定浮标位置时，玩家位置的限制仍未检查
+        let bobber_only = AppConfig { bobber_tile: Some((0, 0)), ..AppConfig::for_test() };
+        assert!(bobber_only.position_allows(&spawn_data));
+        assert!(bobber_only.position_unchecked(&spawn_data));
+    }
+}
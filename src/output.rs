@@ -0,0 +1,771 @@
+//! src/output.rs
+//!
+//! 将结构化报告渲染为不同的输出格式：
+//! 人类阅读的定宽表格，以及便于导入表格软件/仪表盘的 JSON 和 CSV。
+
+use stardew_fishing_prob::crab_pot::CrabPotReport;
+use stardew_fishing_prob::economy::MoneySpot;
+use stardew_fishing_prob::explain::AreaExplanation;
+use stardew_fishing_prob::models::{AppConfig, Rect};
+use stardew_fishing_prob::position::PositionRestriction;
+use stardew_fishing_prob::search::SearchResult;
+use stardew_fishing_prob::simulation::{SegmentSimulation, SimulatedItem, CONFIDENCE_Z};
+use stardew_fishing_prob::report::{ReportMode, SegmentReport, UnavailableItem, TRASH_GROUP_SOURCE_ID};
+use stardew_fishing_prob::timing::SegmentTiming;
+use stardew_fishing_prob::treasure::TreasureLoot;
+use stardew_fishing_prob::summary::{AreaSummary, SummaryScope};
+use stardew_fishing_prob::utils;
+use std::collections::HashSet;
+
+/// 输出格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!("未知的输出格式: {} (可选 table、json、csv)", s)),
+        }
+    }
+}
+
+pub fn print_reports(reports: &[SegmentReport], format: OutputFormat, show_quality: bool, show_treasure: bool, show_unavailable: bool) {
+    match format {
+        OutputFormat::Table => {
+            reports.iter().enumerate().for_each(|(i, report)| {
+                print_table(report);
+                if show_quality { print_quality_table(report); }
+                if show_treasure { print_treasure_table(report); }
+                if show_unavailable && !report.segment_unavailable.is_empty() {
+                    let title = format!("Not available in {} - {}", report.start_time, report.end_time);
+                    print_unavailable_table(&title, &report.segment_unavailable);
+                }
+                // 解析与咬钩阶段被过滤的条目同一 FishArea 的所有时间段相同，在该 FishArea 的最后一个时间段之后打印一次
+                let is_last_of_area = reports.get(i + 1).is_none_or(|next| next.fish_area != report.fish_area);
+                if show_unavailable && is_last_of_area {
+                    let title = format!("Not available in {} ({})", report.location, report.fish_area.as_deref().unwrap_or("Default"));
+                    print_unavailable_table(&title, &report.unavailable);
+                }
+            });
+            // 战利品只取决于配置，所有时间段相同，只打印一次
+            if show_treasure {
+                if let Some(loot) = reports.first().and_then(|report| report.treasure_loot.as_ref()) {
+                    print_treasure_loot_table(loot);
+                }
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(reports).expect("report is serializable")),
+        OutputFormat::Csv => print_csv(reports),
+    }
+}
+
+pub fn print_crab_pot_reports(reports: &[CrabPotReport], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => reports.iter().for_each(print_crab_pot_table),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(reports).expect("report is serializable")),
+        OutputFormat::Csv => print_crab_pot_csv(reports),
+    }
+}
+
+pub fn print_summaries(summaries: &[AreaSummary], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => summaries.iter().for_each(print_summary_table),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(summaries).expect("summary is serializable")),
+        OutputFormat::Csv => print_summary_csv(summaries),
+    }
+}
+
+pub fn print_timing_reports(reports: &[SegmentTiming], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => reports.iter().for_each(print_timing_table),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(reports).expect("timing report is serializable")),
+        OutputFormat::Csv => print_timing_csv(reports),
+    }
+}
+
+/// 每行一个 (地点, FishArea, 时间段, 物品)。
+fn print_timing_csv(reports: &[SegmentTiming]) {
+    println!("location,fish_area,start_time,end_time,seconds_per_cast,casts_per_segment,display_id,name,precedence,final_prob,expected_casts,expected_game_minutes,expected_catches");
+    for report in reports {
+        for item in &report.items {
+            println!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&report.location),
+                csv_field(report.fish_area.as_deref().unwrap_or("")),
+                report.start_time,
+                report.end_time,
+                report.seconds_per_cast,
+                report.casts_per_segment,
+                csv_field(&item.display_id),
+                csv_field(&item.name),
+                item.precedence,
+                item.final_prob,
+                item.expected_casts.map_or(String::new(), |v| v.to_string()),
+                item.expected_game_minutes.map_or(String::new(), |v| v.to_string()),
+                item.expected_catches,
+            );
+        }
+    }
+}
+
+/// 打印一个时间段的耗时表格，垃圾物品聚合为一行。
+fn print_timing_table(report: &SegmentTiming) {
+    let area_name = report.fish_area.as_deref().unwrap_or("Default");
+    println!(
+        "\nLocation: {} ({}) | Time: {} - {} | {:.1}s ({:.1} game min) per cast | {:.0} casts",
+        report.location, area_name, report.start_time, report.end_time,
+        report.seconds_per_cast, report.game_minutes_per_cast, report.casts_per_segment
+    );
+
+    let mut rows: Vec<(&str, i32, f64, f64)> = Vec::new();
+    let mut trash_row: Option<(&str, i32, f64, f64)> = None;
+    for item in &report.items {
+        if item.source_group_id == TRASH_GROUP_SOURCE_ID {
+            let row = trash_row.get_or_insert(("Trash Group", item.precedence, 0.0, 0.0));
+            row.2 += item.final_prob;
+            row.3 += item.expected_catches;
+        } else {
+            rows.push((&item.name, item.precedence, item.final_prob, item.expected_catches));
+        }
+    }
+    rows.extend(trash_row);
+
+    println!("{:<15} | {:<5} | {:>10} | {:>10} | {:>12} | {:>8}", "Name", "Prio", "Final Prob", "Exp. Casts", "Exp. Minutes", "Catches");
+    println!("{:-<15}-+-{:-<5}-+-{:-<10}-+-{:-<10}-+-{:-<12}-+-{:-<8}", "", "", "", "", "", "");
+    for (name, prio, final_prob, catches) in rows {
+        let (casts, minutes) = if final_prob > 0.0 {
+            let casts = 1.0 / final_prob;
+            (format!("{:.1}", casts), format!("{:.0}", casts * report.game_minutes_per_cast))
+        } else {
+            ("-".to_string(), "-".to_string())
+        };
+        println!(
+            "{:<15} | {:<5} | {:>9.2}% | {:>10} | {:>12} | {:>8.2}",
+            utils::truncate_string(name, 13),
+            prio,
+            final_prob * 100.0,
+            casts,
+            minutes,
+            catches
+        );
+    }
+}
+
+pub fn print_money_spots(spots: &[MoneySpot], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => print_money_table(spots),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(spots).expect("money spot is serializable")),
+        OutputFormat::Csv => print_money_csv(spots),
+    }
+}
+
+/// 每行一个 (地点, FishArea, 时间段, 装备组合)。
+fn print_money_csv(spots: &[MoneySpot]) {
+    println!("location,fish_area,start_time,end_time,setup,value_per_cast,casts_per_hour,value_per_hour,unpriced_prob");
+    for s in spots {
+        println!(
+            "{},{},{},{},{},{},{},{},{}",
+            csv_field(&s.location),
+            csv_field(s.fish_area.as_deref().unwrap_or("")),
+            s.start_time,
+            s.end_time,
+            csv_field(&s.setup),
+            s.value_per_cast,
+            s.casts_per_hour,
+            s.value_per_hour,
+            s.unpriced_prob,
+        );
+    }
+}
+
+/// 打印按每小时收益排名的地点。
+fn print_money_table(spots: &[MoneySpot]) {
+    println!("\nBest money spots");
+    if spots.is_empty() {
+        println!("在当前的季节、天气与时间下没有可以钓鱼的地点。");
+        return;
+    }
+
+    println!(
+        "{:<4} | {:<20} | {:<11} | {:<31} | {:>8} | {:>7} | {:>8}",
+        "Rank", "Location", "Time", "Setup", "g/Cast", "Casts/h", "g/Hour"
+    );
+    println!("{:-<4}-+-{:-<20}-+-{:-<11}-+-{:-<31}-+-{:-<8}-+-{:-<7}-+-{:-<8}", "", "", "", "", "", "", "");
+    for (rank, s) in spots.iter().enumerate() {
+        let location = match &s.fish_area {
+            Some(area) => format!("{} ({})", s.location, area),
+            None => s.location.clone(),
+        };
+        println!(
+            "{:<4} | {:<20} | {:>4} - {:<4} | {:<31} | {:>8.1} | {:>7.1} | {:>8.0}",
+            rank + 1,
+            utils::truncate_string(&location, 18),
+            s.start_time,
+            s.end_time,
+            s.setup,
+            s.value_per_cast,
+            s.casts_per_hour,
+            s.value_per_hour
+        );
+    }
+}
+
+pub fn print_position_restrictions(config: &AppConfig, restrictions: &[PositionRestriction], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => print_position_table(config, restrictions),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(restrictions).expect("position restriction is serializable")),
+        OutputFormat::Csv => print_position_csv(restrictions),
+    }
+}
+
+/// 矩形显示为 "(x, y) 宽×高"，没有限制时显示 "-"。
+fn format_rect(rect: Option<&Rect>) -> String {
+    rect.map_or("-".to_string(), |r| format!("({}, {}) {}x{}", r.x, r.y, r.width, r.height))
+}
+
+/// 每行一个带位置限制的条目，矩形拆分为 x、y、宽、高四列。
+fn print_position_csv(restrictions: &[PositionRestriction]) {
+    println!("id,name,fish_area,player_x,player_y,player_width,player_height,bobber_x,bobber_y,bobber_width,bobber_height,allowed");
+    let rect_fields = |rect: Option<&Rect>| rect.map_or(",,,".to_string(), |r| format!("{},{},{},{}", r.x, r.y, r.width, r.height));
+    for r in restrictions {
+        println!(
+            "{},{},{},{},{},{}",
+            csv_field(&r.id),
+            csv_field(&r.name),
+            csv_field(r.fish_area.as_deref().unwrap_or("")),
+            rect_fields(r.player_position.as_ref()),
+            rect_fields(r.bobber_position.as_ref()),
+            r.allowed,
+        );
+    }
+}
+
+/// 打印地点中带位置限制的条目，以及当前位置是否满足。
+fn print_position_table(config: &AppConfig, restrictions: &[PositionRestriction]) {
+    let format_tile = |tile: Option<(i32, i32)>| tile.map_or("未指定".to_string(), |(x, y)| format!("({}, {})", x, y));
+    println!(
+        "\nPosition-restricted entries in {} | Player tile: {} | Bobber tile: {}",
+        config.location_name, format_tile(config.player_tile), format_tile(config.bobber_tile)
+    );
+    if restrictions.is_empty() {
+        println!("该地点没有限制玩家或浮标位置的条目。");
+        return;
+    }
+
+    println!("{:<20} | {:<10} | {:<20} | {:<20} | Allowed", "Name", "Area", "Player Position", "Bobber Position");
+    println!("{:-<20}-+-{:-<10}-+-{:-<20}-+-{:-<20}-+-{:-<7}", "", "", "", "", "");
+    for r in restrictions {
+        println!(
+            "{:<20} | {:<10} | {:<20} | {:<20} | {}",
+            utils::truncate_string(&r.name, 18),
+            r.fish_area.as_deref().unwrap_or("-"),
+            format_rect(r.player_position.as_ref()),
+            format_rect(r.bobber_position.as_ref()),
+            if r.allowed { "yes" } else { "no" }
+        );
+    }
+}
+
+pub fn print_simulation_reports(reports: &[SegmentSimulation], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => reports.iter().for_each(print_simulation_table),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(reports).expect("simulation report is serializable")),
+        OutputFormat::Csv => print_simulation_csv(reports),
+    }
+}
+
+/// 每行一个 (地点, FishArea, 时间段, 物品)，什么都没钓到的一行 display_id 为空。
+fn print_simulation_csv(reports: &[SegmentSimulation]) {
+    println!("location,fish_area,start_time,end_time,casts,seed,display_id,name,exact_prob,observed_prob,catches,ci_low,ci_high,diverges");
+    for report in reports {
+        for item in report.items.iter().chain([&report.nothing]) {
+            println!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&report.location),
+                csv_field(report.fish_area.as_deref().unwrap_or("")),
+                report.start_time,
+                report.end_time,
+                report.casts,
+                report.seed,
+                csv_field(&item.display_id),
+                csv_field(&item.name),
+                item.exact_prob,
+                item.observed_prob,
+                item.catches,
+                item.ci_low,
+                item.ci_high,
+                item.diverges,
+            );
+        }
+    }
+}
+
+/// 打印一个时间段的模拟结果，偏离的行以 `*` 标记。
+fn print_simulation_table(report: &SegmentSimulation) {
+    let area_name = report.fish_area.as_deref().unwrap_or("Default");
+    println!(
+        "\nLocation: {} ({}) | Time: {} - {} | {} casts (seed {}) | {} divergent",
+        report.location, area_name, report.start_time, report.end_time,
+        report.casts, report.seed, report.divergent_count
+    );
+
+    let ci_header = format!("CI (z = {})", CONFIDENCE_Z);
+    println!("{:<15} | {:>10} | {:>10} | {:<20} | Diverges", "Name", "Exact", "Observed", ci_header);
+    println!("{:-<15}-+-{:-<10}-+-{:-<10}-+-{:-<20}-+-{:-<8}", "", "", "", "", "");
+    let print_row = |item: &SimulatedItem| {
+        println!(
+            "{:<15} | {:>9.3}% | {:>9.3}% | [{:>7.3}%, {:>7.3}%] | {}",
+            utils::truncate_string(&item.name, 13),
+            item.exact_prob * 100.0,
+            item.observed_prob * 100.0,
+            item.ci_low * 100.0,
+            item.ci_high * 100.0,
+            if item.diverges { "*" } else { "-" }
+        );
+    };
+    report.items.iter().for_each(print_row);
+    print_row(&report.nothing);
+}
+
+pub fn print_explanations(fish_id: &str, explanations: &[AreaExplanation], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => explanations.iter().for_each(|e| print_explanation(fish_id, e)),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(explanations).expect("explanation is serializable")),
+        OutputFormat::Csv => {
+            println!("location,fish_area,depth,text");
+            for e in explanations {
+                for line in &e.lines {
+                    println!(
+                        "{},{},{},{}",
+                        csv_field(&e.location),
+                        csv_field(e.fish_area.as_deref().unwrap_or("")),
+                        line.depth,
+                        csv_field(&line.text)
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// 按缩进层级打印一个 FishArea 的追踪记录。
+fn print_explanation(fish_id: &str, explanation: &AreaExplanation) {
+    println!(
+        "\nExplain: {} | Location: {} ({})",
+        fish_id, explanation.location, explanation.fish_area.as_deref().unwrap_or("Default")
+    );
+    for line in &explanation.lines {
+        println!("{}{}", "  ".repeat(line.depth), line.text);
+    }
+}
+
+pub fn print_search_results(fish_id: &str, results: &[SearchResult], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => print_search_table(fish_id, results),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(results).expect("search result is serializable")),
+        OutputFormat::Csv => print_search_csv(results),
+    }
+}
+
+/// 每行一个 (地点, FishArea, 季节, 天气, 时间段) 的最佳组合。
+fn print_search_csv(results: &[SearchResult]) {
+    println!("location,fish_area,season,weather,start_time,end_time,setup,bait_item_id,has_curiosity_lure,is_training_rod,final_prob");
+    for r in results {
+        println!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&r.location),
+            csv_field(r.fish_area.as_deref().unwrap_or("")),
+            r.season,
+            r.weather,
+            r.start_time,
+            r.end_time,
+            csv_field(&r.setup),
+            csv_field(r.bait_item_id.as_deref().unwrap_or("")),
+            r.has_curiosity_lure,
+            r.is_training_rod,
+            r.final_prob,
+        );
+    }
+}
+
+/// 打印按概率排名的查询结果。
+fn print_search_table(fish_id: &str, results: &[SearchResult]) {
+    println!("\nBest spots for {}", fish_id);
+    if results.is_empty() {
+        println!("在当前的钓鱼等级、水深等设置下，任何地点都钓不到该鱼。");
+        return;
+    }
+
+    println!(
+        "{:<4} | {:<20} | {:<7} | {:<7} | {:<11} | {:<31} | Final Prob",
+        "Rank", "Location", "Season", "Weather", "Time", "Setup"
+    );
+    println!("{:-<4}-+-{:-<20}-+-{:-<7}-+-{:-<7}-+-{:-<11}-+-{:-<31}-+-{:-<12}", "", "", "", "", "", "", "");
+    for (rank, r) in results.iter().enumerate() {
+        let location = match &r.fish_area {
+            Some(area) => format!("{} ({})", r.location, area),
+            None => r.location.clone(),
+        };
+        println!(
+            "{:<4} | {:<20} | {:<7} | {:<7} | {:>4} - {:<4} | {:<31} | {:>10.2}%",
+            rank + 1,
+            utils::truncate_string(&location, 18),
+            r.season,
+            r.weather,
+            r.start_time,
+            r.end_time,
+            r.setup,
+            r.final_prob * 100.0
+        );
+    }
+}
+
+/// 每行一个 (地点, FishArea, 物品)。
+fn print_summary_csv(summaries: &[AreaSummary]) {
+    println!("location,fish_area,scope,season,weather,display_id,name,precedence,mean_prob,catch_share");
+    for summary in summaries {
+        let scope = match summary.scope {
+            SummaryScope::Day => "day",
+            SummaryScope::Season => "season",
+        };
+        for item in &summary.items {
+            println!(
+                "{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&summary.location),
+                csv_field(summary.fish_area.as_deref().unwrap_or("")),
+                scope,
+                summary.season,
+                summary.weather.as_deref().unwrap_or(""),
+                csv_field(&item.display_id),
+                csv_field(&item.name),
+                item.precedence,
+                item.mean_prob,
+                item.catch_share,
+            );
+        }
+    }
+}
+
+/// 打印一个 FishArea 的汇总表格，垃圾物品聚合为一行。
+fn print_summary_table(summary: &AreaSummary) {
+    let area_name = summary.fish_area.as_deref().unwrap_or("Default");
+    let scope = match (&summary.scope, &summary.weather) {
+        (SummaryScope::Day, Some(weather)) => format!("Whole Day ({} {})", summary.season, weather),
+        _ => format!("Whole Season ({}, all weather)", summary.season),
+    };
+    println!(
+        "\nLocation: {} ({}) | {} | Catch Prob: {:.2}%",
+        summary.location, area_name, scope, summary.catch_prob * 100.0
+    );
+
+    println!("{:<15} | {:<5} | {:>12} | {:>12}", "Name", "Prio", "Mean Prob", "Catch Share");
+    println!("{:-<15}-+-{:-<5}-+-{:-<12}-+-{:-<12}", "", "", "", "");
+    let mut rows: Vec<(&str, i32, f64, f64)> = Vec::new();
+    let mut trash_row: Option<(&str, i32, f64, f64)> = None;
+    for item in &summary.items {
+        if item.source_group_id == TRASH_GROUP_SOURCE_ID {
+            let row = trash_row.get_or_insert(("Trash Group", item.precedence, 0.0, 0.0));
+            row.2 += item.mean_prob;
+            row.3 += item.catch_share;
+        } else {
+            rows.push((&item.name, item.precedence, item.mean_prob, item.catch_share));
+        }
+    }
+    rows.extend(trash_row);
+
+    for (name, prio, mean_prob, catch_share) in rows {
+        println!(
+            "{:<15} | {:<5} | {:>11.2}% | {:>11.2}%",
+            utils::truncate_string(name, 13),
+            prio,
+            mean_prob * 100.0,
+            catch_share * 100.0
+        );
+    }
+}
+
+/// 每行一个 (地点, FishArea, 物品)。
+fn print_crab_pot_csv(reports: &[CrabPotReport]) {
+    println!("location,fish_area,fish_types,junk_chance,display_id,name,is_junk,probability");
+    for report in reports {
+        for item in &report.items {
+            println!(
+                "{},{},{},{},{},{},{},{}",
+                csv_field(&report.location),
+                csv_field(report.fish_area.as_deref().unwrap_or("")),
+                csv_field(&report.fish_types.join(" ")),
+                report.junk_chance,
+                csv_field(&item.display_id),
+                csv_field(&item.name),
+                item.is_junk,
+                item.probability,
+            );
+        }
+    }
+}
+
+/// 打印一个 FishArea 的蟹笼表格，垃圾聚合为一行。
+fn print_crab_pot_table(report: &CrabPotReport) {
+    let area_name = report.fish_area.as_deref().unwrap_or("Default");
+    println!(
+        "\nLocation: {} ({}) | Types: {} | Junk Chance: {:.0}% | Crab Pot Mode",
+        report.location, area_name, report.fish_types.join(" "), report.junk_chance * 100.0
+    );
+    if !report.is_active {
+        println!("蟹笼没有鱼饵（且没有诱饵大师职业），不会捕获任何物品。");
+        return;
+    }
+
+    println!("{:<15} | Daily Prob", "Name");
+    println!("{:-<15}-+-{:-<15}", "", "");
+    for item in report.items.iter().filter(|i| !i.is_junk) {
+        println!("{:<15} | {:>12.2}%", utils::truncate_string(&item.name, 13), item.probability * 100.0);
+    }
+    let junk_prob: f64 = report.items.iter().filter(|i| i.is_junk).map(|i| i.probability).sum();
+    println!("{:<15} | {:>12.2}%", "Trash Group", junk_prob * 100.0);
+}
+
+/// 每行一个 (地点, FishArea, 时间段, 场景, 物品)，名称不做截断。
+fn print_csv(reports: &[SegmentReport]) {
+    println!("location,fish_area,start_time,end_time,mode,scenario,display_id,name,precedence,source_group_id,source,get_chance_prob,bite_chance_prob,final_prob,catch_success_prob,landed_prob,normal,silver,gold,iridium,mean_size,unavailable_reason");
+    for (i, report) in reports.iter().enumerate() {
+        let mode = match report.mode {
+            ReportMode::MagicBait => "magic_bait",
+            ReportMode::Comparison => "comparison",
+        };
+        for scenario in &report.scenarios {
+            for p in &scenario.probabilities {
+                println!(
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},",
+                    csv_field(&report.location),
+                    csv_field(report.fish_area.as_deref().unwrap_or("")),
+                    report.start_time,
+                    report.end_time,
+                    mode,
+                    csv_field(&scenario.name),
+                    csv_field(&p.display_id),
+                    csv_field(&p.name),
+                    p.precedence,
+                    csv_field(&p.source_group_id),
+                    csv_field(&p.source),
+                    p.get_chance_prob,
+                    p.bite_chance_prob,
+                    p.final_prob,
+                    p.catch_success_prob.map_or(String::new(), |v| v.to_string()),
+                    p.landed_prob.map_or(String::new(), |v| v.to_string()),
+                    p.quality.as_ref().map_or(String::new(), |q| q.normal.to_string()),
+                    p.quality.as_ref().map_or(String::new(), |q| q.silver.to_string()),
+                    p.quality.as_ref().map_or(String::new(), |q| q.gold.to_string()),
+                    p.quality.as_ref().map_or(String::new(), |q| q.iridium.to_string()),
+                    p.quality.as_ref().map_or(String::new(), |q| q.mean_size.to_string()),
+                );
+            }
+        }
+
+        // 钓不到的条目没有场景与概率，只填写物品、来源与原因；整个 FishArea 共有的条目不填时间段，只输出一次
+        let time = (report.start_time.to_string(), report.end_time.to_string());
+        let is_last_of_area = reports.get(i + 1).is_none_or(|next| next.fish_area != report.fish_area);
+        let area_rows = report.unavailable.iter().filter(|_| is_last_of_area).map(|item| (item, (String::new(), String::new())));
+        for (item, (start_time, end_time)) in report.segment_unavailable.iter().map(|item| (item, time.clone())).chain(area_rows) {
+            println!(
+                "{},{},{},{},{},,{},{},,,{},,,,,,,,,,,{}",
+                csv_field(&report.location),
+                csv_field(report.fish_area.as_deref().unwrap_or("")),
+                start_time,
+                end_time,
+                mode,
+                csv_field(&item.display_id),
+                csv_field(&item.name),
+                csv_field(&item.source),
+                csv_field(&item.reason.to_string()),
+            );
+        }
+    }
+}
+
+/// 按 RFC 4180 转义 CSV 字段。
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// 打印每个场景的宝箱概率。
+fn print_treasure_table(report: &SegmentReport) {
+    println!("{:<15} | {:>9} | {:>9} | {:>11}", "Treasure", "Per Catch", "Per Cast", "Golden/Cast");
+    println!("{:-<15}-+-{:-<9}-+-{:-<9}-+-{:-<11}", "", "", "", "");
+    for scenario in &report.scenarios {
+        let t = &scenario.treasure;
+        println!(
+            "{:<15} | {:>8.2}% | {:>8.2}% | {:>10.2}%",
+            utils::truncate_string(&scenario.name, 13),
+            t.per_catch * 100.0,
+            t.per_cast * 100.0,
+            t.golden_per_cast * 100.0
+        );
+    }
+}
+
+/// 打印因当前配置而钓不到的条目及原因。
+fn print_unavailable_table(title: &str, items: &[UnavailableItem]) {
+    println!("\n{} | {} entries", title, items.len());
+    if items.is_empty() { return; }
+    println!("{:<15} | {:<15} | Reason", "Name", "Source");
+    println!("{:-<15}-+-{:-<15}-+-{:-<30}", "", "", "");
+    for item in items {
+        println!("{:<15} | {:<15} | {}", utils::truncate_string(&item.name, 13), item.source, item.reason);
+    }
+}
+
+/// 打印宝箱中各战利品的期望数量。
+fn print_treasure_loot_table(loot: &TreasureLoot) {
+    println!(
+        "\nTreasure loot per chest | {:.2} rolls (golden chest: {:.2} rolls)",
+        loot.expected_rolls, loot.golden_expected_rolls
+    );
+    println!("{:<20} | {:<18} | {:>8} | {:>8}", "Item", "Category", "Normal", "Golden");
+    println!("{:-<20}-+-{:-<18}-+-{:-<8}-+-{:-<8}", "", "", "", "");
+    for item in &loot.items {
+        println!(
+            "{:<20} | {:<18} | {:>8.3} | {:>8.3}",
+            item.name, item.category, item.expected_quantity, item.golden_expected_quantity
+        );
+    }
+}
+
+/// 打印一个时间段中第一个场景（用户的钓竿）下各条鱼的品质分布。
+fn print_quality_table(report: &SegmentReport) {
+    let Some(first_scenario) = report.scenarios.first() else { return; };
+    let mut handled_source_groups: HashSet<&str> = HashSet::new();
+
+    println!("{:<15} | {:>8} | {:>8} | {:>8} | {:>8} | Mean Size", "Quality", "Normal", "Silver", "Gold", "Iridium");
+    println!("{:-<15}-+-{:-<8}-+-{:-<8}-+-{:-<8}-+-{:-<8}-+-{:-<10}", "", "", "", "", "", "");
+    for p in &first_scenario.probabilities {
+        let Some(quality) = &p.quality else { continue; };
+        if !handled_source_groups.insert(&p.source_group_id) { continue; }
+        println!(
+            "{:<15} | {:>7.1}% | {:>7.1}% | {:>7.1}% | {:>7.1}% | {:>6.1} in",
+            utils::truncate_string(&p.name, 13),
+            quality.normal * 100.0,
+            quality.silver * 100.0,
+            quality.gold * 100.0,
+            quality.iridium * 100.0,
+            quality.mean_size
+        );
+    }
+}
+
+/// 打印一个时间段的定宽表格。垃圾物品会被聚合为一行，名称会被截断。
+fn print_table(report: &SegmentReport) {
+    let area_name = report.fish_area.as_deref().unwrap_or("Default");
+    let mode_name = match report.mode {
+        ReportMode::MagicBait => "Magic Bait Mode",
+        ReportMode::Comparison => "Comparison Mode",
+    };
+    println!(
+        "\nLocation: {} ({}) | Time: {} - {} | {}",
+        report.location, area_name, report.start_time, report.end_time, mode_name
+    );
+
+    // 所有场景的物品顺序一致，以第一个场景作为行的顺序
+    let Some(first_scenario) = report.scenarios.first() else { return; };
+
+    // 启用小游戏模拟时，额外显示用户钓竿（第一个场景）下成功钓起的概率
+    let show_landing = first_scenario.probabilities.iter().any(|p| p.catch_success_prob.is_some());
+
+    let mut aggregated_rows: Vec<(String, i32, Vec<f64>, f64, String)> = Vec::new();
+    let mut trash_aggregator: Option<(String, i32, Vec<f64>, f64, String)> = None;
+    let mut trash_sources: Vec<&str> = Vec::new();
+    let mut handled_source_groups: HashSet<&str> = HashSet::new();
+
+    for (row, item) in first_scenario.probabilities.iter().enumerate() {
+        let probs: Vec<f64> = report.scenarios.iter().map(|s| s.probabilities[row].final_prob).collect();
+        let success = item.catch_success_prob.unwrap_or(1.0);
+
+        if item.source_group_id == TRASH_GROUP_SOURCE_ID {
+            let (_, _, agg_probs, _, _) = trash_aggregator.get_or_insert_with(|| {
+                ("Trash Group".to_string(), item.precedence, vec![0.0; report.scenarios.len()], 1.0, String::new())
+            });
+            for (i, prob) in probs.iter().enumerate() { agg_probs[i] += prob; }
+            if !trash_sources.contains(&item.source.as_str()) { trash_sources.push(&item.source); }
+        } else if handled_source_groups.insert(&item.source_group_id) {
+            // 没有按位置过滤的条目在名称后标记 *
+            let name = if report.position_unchecked.contains(&item.source) {
+                format!("{}*", utils::truncate_string(&item.name, 12))
+            } else {
+                item.name.clone()
+            };
+            aggregated_rows.push((name, item.precedence, probs, success, item.source.clone()));
+        }
+    }
+
+    // 垃圾组可能来自多个条目，列出所有来源
+    if let Some(mut agg_trash) = trash_aggregator {
+        agg_trash.4 = trash_sources.join(",");
+        aggregated_rows.push(agg_trash);
+    }
+    aggregated_rows.sort_by_key(|(_, prio, _, _, _)| *prio);
+
+    match report.mode {
+        ReportMode::MagicBait if show_landing => {
+            println!("{:<15} | {:<5} | {:<13} | {:<13} | {:<13} | Source", "Name", "Prio", "Final Prob", "Catch Success", "Landed");
+            println!("{:-<15}-+-{:-<5}-+-{:-<13}-+-{:-<13}-+-{:-<13}-+-{:-<15}", "", "", "", "", "", "");
+            for (name, prio, probs, success, source) in &aggregated_rows {
+                println!(
+                    "{:<15} | {:<5} | {:>12.2}% | {:>12.2}% | {:>12.2}% | {}",
+                    utils::truncate_string(name, 13),
+                    prio,
+                    probs[0] * 100.0,
+                    success * 100.0,
+                    probs[0] * success * 100.0,
+                    source
+                );
+            }
+        }
+        ReportMode::MagicBait => {
+            println!("{:<15} | {:<5} | {:<13} | Source", "Name", "Prio", "Final Prob");
+            println!("{:-<15}-+-{:-<5}-+-{:-<13}-+-{:-<15}", "", "", "", "");
+            for (name, prio, probs, _, source) in &aggregated_rows {
+                println!(
+                    "{:<15} | {:<5} | {:>12.2}% | {}",
+                    utils::truncate_string(name, 13),
+                    prio,
+                    probs[0] * 100.0,
+                    source
+                );
+            }
+        }
+        ReportMode::Comparison => {
+            print!("{:<15}|{:<6}|", "Item", "Prio");
+            if show_landing { print!("{:<8}|", "Success"); }
+            for scenario in &report.scenarios {
+                print!("{:<12}|", utils::truncate_string(&scenario.name, 10));
+            }
+            println!(" Source");
+
+            for (name, prio, probs, success, source) in &aggregated_rows {
+                print!("{:<15}| {:<5}|", utils::truncate_string(name, 13), prio);
+                if show_landing { print!(" {:>6.1}%|", success * 100.0); }
+                for prob in probs {
+                    print!(" {:>10.2}%|", prob * 100.0);
+                }
+                println!(" {}", source);
+            }
+        }
+    }
+
+    if aggregated_rows.iter().any(|(_, _, _, _, source)| report.position_unchecked.iter().any(|s| s == source)) {
+        println!("* 带有 PlayerPosition / BobberPosition 限制；未指定 --player-tile / --bobber-tile，按满足计算");
+    }
+}
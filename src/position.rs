@@ -0,0 +1,45 @@
+//! src/position.rs
+//!
+//! 位置限制：Locations.json 中部分刷鱼条目带有 PlayerPosition / BobberPosition，
+//! 只有玩家或浮标处在对应的矩形内时才会参与刷鱼（例如海滩东侧码头的传说之鱼）。
+//! 配置中指定 player_tile / bobber_tile 后，解析器会按这些矩形过滤条目；
+//! 这里列出一个地点中所有带位置限制的条目，便于确定应该站在哪里。
+
+use crate::models::{AppConfig, GameData, Rect};
+use serde::Serialize;
+
+/// 一个带位置限制的刷鱼条目。
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionRestriction {
+    /// 条目的 Id，没有 Id 时为物品 Id
+    pub id: String,
+    pub name: String,
+    pub fish_area: Option<String>,
+    pub player_position: Option<Rect>,
+    pub bobber_position: Option<Rect>,
+    /// 当前配置的位置是否满足限制；未指定的位置视为满足
+    pub allowed: bool,
+}
+
+/// 列出配置中地点（以及 Default）所有带位置限制的条目，按文件中的顺序排列。
+pub fn list_position_restrictions(config: &AppConfig, game_data: &GameData) -> Vec<PositionRestriction> {
+    let Some(location_data) = game_data.locations.get(&config.location_name) else { return vec![]; };
+
+    game_data.locations["Default"].fish.iter()
+        .chain(location_data.fish.iter())
+        .filter(|spawn_data| spawn_data.player_position.is_some() || spawn_data.bobber_position.is_some())
+        .map(|spawn_data| {
+            let item_id = spawn_data.item_id.clone()
+                .or_else(|| spawn_data.random_item_id.as_ref().map(|ids| ids.join("|")))
+                .unwrap_or_default();
+            PositionRestriction {
+                id: spawn_data.id.clone().unwrap_or_else(|| item_id.clone()),
+                name: game_data.fish.get(&item_id).map_or(item_id, |fish| fish.name.clone()),
+                fish_area: spawn_data.fish_area_id.clone(),
+                player_position: spawn_data.player_position.clone(),
+                bobber_position: spawn_data.bobber_position.clone(),
+                allowed: config.position_allows(spawn_data),
+            }
+        })
+        .collect()
+}
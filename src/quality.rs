@@ -0,0 +1,167 @@
+//! src/quality.rs
+//!
+//! 钓起的鱼的尺寸与品质分布。
+//!
+//! 对应游戏中 FishingRod 与 BobberBar 的逻辑：
+//! 尺寸系数 = 水深 / 5 × Random(1 + 钓鱼等级 / 2, max(6, 1 + 钓鱼等级 / 2)) / 5 × (1 + Random(-10, 10) / 100)，
+//! 截断到 [0, 1]；豪华鱼饵再乘以 1.2。
+//! 系数 < 0.33 为普通，< 0.66 为银星，否则为金星；品质浮标与完美捕获各提升一级（金星提升为铱星）。
+//! 尺寸（英寸）= ⌊最小尺寸 + (最大尺寸 - 最小尺寸) × 系数⌋ + 1。
+//! 训练钓竿钓到的鱼总是普通品质、最小尺寸。
+//!
+//! 挑战鱼饵不参与以上计算：游戏中它只在 BobberBar 里作为 challengeBaitFishes 计数，
+//! 鱼每离开钓鱼条一次减一，最后决定钓起的数量（见 `Bait::expected_fish_count` 与 `minigame`）；
+//! 尺寸系数与品质只读取豪华鱼饵的加成。挑战鱼饵对品质唯一的间接影响是它让完美捕获更难，
+//! 这一点已经体现在 `perfect_rate` 中。
+//!
+//! 随机项的取值都是有限个整数，因此分布可以精确枚举，不需要模拟。
+
+use crate::minigame::MinigameEstimator;
+use crate::models::{AppConfig, Bait, FishQuality, GameData, ParsedFishData, ProbabilityDetails, Tackle};
+use std::collections::BTreeMap;
+
+
+/// 计算一条鱼的尺寸与品质分布。`perfect_rate` 为成功钓起时完美捕获的概率。
+pub fn fish_quality(fish: &ParsedFishData, config: &AppConfig, perfect_rate: f64) -> FishQuality {
+    if config.is_training_rod() {
+        let size = fish.min_size + 1;
+        return FishQuality {
+            normal: 1.0,
+            silver: 0.0,
+            gold: 0.0,
+            iridium: 0.0,
+            mean_size: size as f64,
+            size_distribution: vec![(size, 1.0)],
+        };
+    }
+
+    let depth_factor = config.water_depth.min(5) as f64 / 5.0;
+    let min_contribution = 1 + config.fishing_level as i32 / 2;
+    let max_contribution = 6.max(min_contribution);
+    // Random.Next(a, b) 在 a == b 时返回 a
+    let contributions: Vec<i32> = if min_contribution < max_contribution {
+        (min_contribution..max_contribution).collect()
+    } else {
+        vec![min_contribution]
+    };
+    let bait_factor = config.bait().map_or(1.0, Bait::size_multiplier);
+    let quality_bobbers = config.tackle_count(Tackle::QualityBobber) as usize;
+
+    // 品质等级：0 普通，1 银星，2 金星，3 铱星
+    let mut tiers = [0.0; 4];
+    let mut sizes: BTreeMap<u32, f64> = BTreeMap::new();
+    let combination_prob = 1.0 / (contributions.len() * 21) as f64;
+
+    for &contribution in &contributions {
+        for variance in -10..=10 {
+            let size_factor = (depth_factor * contribution as f64 / 5.0 * bait_factor * (1.0 + variance as f64 / 100.0))
+                .clamp(0.0, 1.0);
+
+            let base_tier = if size_factor < 0.33 { 0 } else if size_factor < 0.66 { 1 } else { 2 };
+            let tier = (base_tier + quality_bobbers).min(3);
+            tiers[tier] += combination_prob * (1.0 - perfect_rate);
+            tiers[(tier + 1).min(3)] += combination_prob * perfect_rate;
+
+            let size = (fish.min_size as f64 + fish.max_size.saturating_sub(fish.min_size) as f64 * size_factor) as u32 + 1;
+            *sizes.entry(size).or_insert(0.0) += combination_prob;
+        }
+    }
+
+    FishQuality {
+        normal: tiers[0],
+        silver: tiers[1],
+        gold: tiers[2],
+        iridium: tiers[3],
+        mean_size: sizes.iter().map(|(&size, &prob)| size as f64 * prob).sum(),
+        size_distribution: sizes.into_iter().collect(),
+    }
+}
+
+/// 为一组概率结果填入 quality。启用小游戏模拟时使用模拟得到的完美捕获率，
+/// 否则使用配置中假设的 perfect_catch_rate。
+pub fn apply(
+    probabilities: &mut [ProbabilityDetails],
+    config: &AppConfig,
+    game_data: &GameData,
+    minigame: &mut MinigameEstimator,
+) {
+    for p in probabilities {
+        let Some(fish) = game_data.fish.get(&p.display_id).filter(|fish| !fish.is_algae()) else { continue; };
+        let perfect_rate = if config.minigame.enabled {
+            minigame.outcome(&p.display_id, config, game_data).map_or(0.0, |outcome| outcome.perfect_rate)
+        } else {
+            config.perfect_catch_rate
+        };
+        p.quality = Some(fish_quality(fish, config, perfect_rate));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MotionType, Rod};
+
+    fn fish(min_size: u32, max_size: u32) -> ParsedFishData {
+        ParsedFishData {
+            name: "Test Fish".to_string(),
+            difficulty: 50,
+            motion_type: MotionType::Mixed,
+            min_size,
+            max_size,
+            time_windows: vec![(600, 2600)],
+            seasons: vec![],
+            weather: "both".to_string(),
+            max_depth: 4,
+            min_fishing_level: 0,
+            base_chance: 0.5,
+            depth_multiplier: 0.1,
+            is_tutorial_fish: false,
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-12, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn training_rod_gives_normal_quality_and_min_size() {
+        let config = AppConfig { rod: Rod::TrainingRod, ..AppConfig::for_test() };
+        let quality = fish_quality(&fish(12, 30), &config, 1.0);
+        assert_eq!((quality.normal, quality.silver, quality.gold, quality.iridium), (1.0, 0.0, 0.0, 0.0));
+        assert_eq!(quality.size_distribution, vec![(13, 1.0)]);
+    }
+
+    #[test]
+    fn quality_bobber_raises_one_tier() {
+        // 钓鱼等级 10 时随机项只有 6，水深 3 的尺寸系数为 0.72 × (1 ± 10%)
+        let config = AppConfig { water_depth: 3, ..AppConfig::for_test() };
+        let base = fish_quality(&fish(12, 30), &config, 0.0);
+        assert_close(base.silver, 2.0 / 21.0);
+        assert_close(base.gold, 19.0 / 21.0);
+
+        let bobber = AppConfig { tackles: vec![Tackle::QualityBobber], ..config };
+        let raised = fish_quality(&fish(12, 30), &bobber, 0.0);
+        assert_close(raised.normal, 0.0);
+        assert_close(raised.gold, base.silver);
+        assert_close(raised.iridium, base.gold);
+    }
+
+    #[test]
+    fn deluxe_bait_scales_size_factor() {
+        let config = AppConfig { water_depth: 3, ..AppConfig::for_test() };
+        let deluxe = AppConfig { bait_item_id: Some(Bait::Deluxe.item_id().to_string()), ..config.clone() };
+        let base = fish_quality(&fish(0, 10_000), &config, 0.0);
+        let boosted = fish_quality(&fish(0, 10_000), &deluxe, 0.0);
+
+        // 0.864 × (1 ± 10%) 全部不低于 0.66
+        assert_close(boosted.gold, 1.0);
+        assert!(((boosted.mean_size - 1.0) / (base.mean_size - 1.0) - 1.2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn challenge_bait_does_not_change_quality() {
+        let config = AppConfig { water_depth: 3, ..AppConfig::for_test() };
+        let challenge = AppConfig { bait_item_id: Some(Bait::Challenge.item_id().to_string()), ..config.clone() };
+        assert_eq!(fish_quality(&fish(12, 30), &config, 0.3), fish_quality(&fish(12, 30), &challenge, 0.3));
+    }
+}
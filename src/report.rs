@@ -0,0 +1,235 @@
+//! src/report.rs
+//!
+//! 将一个地点的全部计算结果整理为结构化的报告，
+//! 供表格、JSON、CSV 等不同的输出格式使用。
+
+use crate::calculator;
+use crate::minigame::MinigameEstimator;
+use crate::quality;
+use crate::treasure::{self, TreasureLoot, TreasureOdds};
+use crate::models::{AppConfig, Bait, ExcludedEntry, ExclusionReason, GameData, Rod, ProbabilityDetails, ResolvedItem};
+use crate::utils;
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Locations.json 中垃圾物品组的 Id，表格输出时会被聚合为一行。
+pub const TRASH_GROUP_SOURCE_ID: &str = "(O)167|(O)168|(O)169|(O)170|(O)171|(O)172";
+
+/// 一个时间段使用的计算模式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportMode {
+    /// 魔法鱼饵：只计算当前配置这一种场景
+    MagicBait,
+    /// 对比模式：标准、训练钓竿以及每种特制鱼饵的场景
+    Comparison,
+}
+
+/// 一个场景（某种钓竿/鱼饵组合）下所有物品的概率。
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioReport {
+    pub name: String,
+    pub bait_item_id: Option<String>,
+    pub bait_target_fish_id: Option<String>,
+    /// 按优先级排序，所有场景中的物品顺序一致
+    pub probabilities: Vec<ProbabilityDetails>,
+    pub treasure: TreasureOdds,
+}
+
+/// 一个地点、一个 FishArea、一个时间段的完整结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentReport {
+    pub location: String,
+    pub fish_area: Option<String>,
+    pub start_time: u32,
+    pub end_time: u32,
+    pub mode: ReportMode,
+    pub scenarios: Vec<ScenarioReport>,
+    /// 宝箱战利品的期望数量，只取决于配置；没有 TreasureLoot.json 时为 `None`
+    pub treasure_loot: Option<TreasureLoot>,
+    /// 该 FishArea 中在解析阶段被过滤掉、或因钓竿与 Fish.json 的要求不会咬钩的条目，同一 FishArea 的所有时间段相同
+    pub unavailable: Vec<UnavailableItem>,
+    /// 解析出的物品中，在该时间段被 Fish.json 时间窗口或 Condition（如 TIME）过滤掉的物品
+    pub segment_unavailable: Vec<UnavailableItem>,
+    /// 带有位置限制、但因为没有指定 player_tile / bobber_tile 而按满足处理的条目来源
+    pub position_unchecked: Vec<String>,
+}
+
+/// 一个因当前配置而钓不到的刷鱼条目。
+#[derive(Debug, Clone, Serialize)]
+pub struct UnavailableItem {
+    /// 条目的 ItemId；RandomItemId 条目为以 `|` 连接的候选物品
+    pub display_id: String,
+    pub name: String,
+    pub source: String,
+    pub reason: ExclusionReason,
+}
+
+/// 遍历配置中地点的所有 FishArea 与时间段，生成完整报告。
+pub fn build_location_report(app_config: &AppConfig, game_data: &GameData) -> Vec<SegmentReport> {
+    let mut reports = Vec::new();
+    let mut minigame = MinigameEstimator::new();
+    let treasure_loot = treasure::expected_loot(app_config, game_data);
+
+    for area_id in calculator::get_fish_area_ids(&app_config.location_name, game_data) {
+        let resolution = calculator::resolve_fish_list(app_config, game_data, &area_id, false);
+        let base_items = resolution.included;
+        let bite_excluded = base_items.iter().filter_map(|item| {
+            calculator::bite_exclusion(item, app_config, game_data).map(|reason| resolved_unavailable_item(item, reason, game_data))
+        });
+        let unavailable: Vec<UnavailableItem> = resolution.excluded.iter()
+            .map(|entry| unavailable_item(entry, game_data))
+            .chain(bite_excluded)
+            .collect();
+        let position_unchecked: Vec<String> = base_items.iter()
+            .filter(|item| app_config.position_unchecked(item.source_data))
+            .map(|item| item.source.to_string())
+            .collect();
+        let time_segments = utils::calculate_time_segments(&base_items, game_data);
+
+        for segment in time_segments {
+            if !utils::includes_configured_time(segment, app_config) { continue; }
+            let segment_items = calculator::filter_items_for_time_segment(segment, &base_items, app_config, game_data);
+            if segment_items.is_empty() { continue; }
+            let segment_unavailable: Vec<UnavailableItem> = base_items.iter()
+                .filter_map(|item| {
+                    calculator::segment_exclusion(segment, item, app_config, game_data)
+                        .map(|reason| resolved_unavailable_item(item, reason, game_data))
+                })
+                .collect();
+            let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+
+            // 根据是否为魔法鱼饵选择不同的场景集合
+            let (mode, scenarios) = if app_config.bait() == Some(Bait::Magic) {
+                (ReportMode::MagicBait, vec![("Magic Bait".to_string(), app_config.clone())])
+            } else {
+                (ReportMode::Comparison, build_comparison_scenarios(&segment_items, app_config, game_data))
+            };
+
+            let scenarios = scenarios.into_iter().map(|(name, scenario_config)| {
+                let mut probabilities = calculator::calculate_final_probabilities(&segment_items, &scenario_config, game_data);
+                if app_config.minigame.enabled {
+                    minigame.apply(&mut probabilities, &scenario_config, game_data);
+                }
+                quality::apply(&mut probabilities, &scenario_config, game_data, &mut minigame);
+                ScenarioReport {
+                    name,
+                    bait_item_id: scenario_config.bait_item_id.clone(),
+                    bait_target_fish_id: scenario_config.bait_target_fish_id.clone(),
+                    treasure: treasure::treasure_odds(&probabilities, &scenario_config, game_data),
+                    probabilities: sort_by_row_order(probabilities, &segment_items),
+                }
+            }).collect();
+
+            reports.push(SegmentReport {
+                location: app_config.location_name.clone(),
+                fish_area: area_id.clone(),
+                start_time: segment.0,
+                end_time: segment.1,
+                mode,
+                scenarios,
+                treasure_loot: treasure_loot.clone(),
+                unavailable: unavailable.clone(),
+                segment_unavailable,
+                position_unchecked: position_unchecked.clone(),
+            });
+        }
+    }
+
+    reports
+}
+
+/// 将被过滤掉的条目整理为报告中的一行，物品名称优先取 Fish.json 中的名称。
+fn unavailable_item(entry: &ExcludedEntry, game_data: &GameData) -> UnavailableItem {
+    let ids: Vec<&str> = match (&entry.source_data.item_id, &entry.source_data.random_item_id) {
+        (Some(id), _) => vec![id.as_str()],
+        (None, Some(random_ids)) => random_ids.iter().map(String::as_str).collect(),
+        (None, None) => vec![],
+    };
+    let name = ids.iter()
+        .map(|id| game_data.fish.get(*id).map_or(*id, |fish| fish.name.as_str()))
+        .join("|");
+    UnavailableItem {
+        display_id: ids.join("|"),
+        name,
+        source: entry.source.to_string(),
+        reason: entry.reason.clone(),
+    }
+}
+
+/// 将解析出、但在之后的阶段被过滤掉的物品整理为报告中的一行。
+fn resolved_unavailable_item(item: &ResolvedItem, reason: ExclusionReason, game_data: &GameData) -> UnavailableItem {
+    UnavailableItem {
+        display_id: item.display_id.clone(),
+        name: calculator::get_resolved_item_name(item, game_data),
+        source: item.source.to_string(),
+        reason,
+    }
+}
+
+/// 构建对比模式下的所有场景：标准、训练钓竿，以及针对每条鱼的特制鱼饵。
+///
+/// - 标准：用户的钓竿、钓具与鱼饵，不针对任何鱼；用户装备特制鱼饵时改为不装鱼饵，
+///   因为没有目标的特制鱼饵没有意义；
+/// - 训练钓竿：游戏中训练钓竿没有鱼饵与钓具槽位，因此不装鱼饵与钓具，刷鱼循环只有 1 次。
+/// - 特制鱼饵：用户的钓竿没有鱼饵槽位时改用铱金钓竿，并在场景名后注明钓竿。
+fn build_comparison_scenarios(
+    segment_items: &[&ResolvedItem],
+    app_config: &AppConfig,
+    game_data: &GameData,
+) -> Vec<(String, AppConfig)> {
+    let mut scenarios = Vec::new();
+
+    // 标准场景沿用用户的鱼饵，但不针对任何鱼
+    let mut standard_config = app_config.clone();
+    if standard_config.bait() == Some(Bait::Targeted) {
+        standard_config.bait_item_id = None;
+    }
+    standard_config.bait_target_fish_id = None;
+    scenarios.push(("Standard".to_string(), standard_config.clone()));
+
+    let mut training_rod_config = app_config.clone();
+    training_rod_config.rod = Rod::TrainingRod;
+    training_rod_config.bait_item_id = None;
+    training_rod_config.bait_target_fish_id = None;
+    training_rod_config.tackles.clear();
+    scenarios.push(("TrainingRod".to_string(), training_rod_config));
+
+    let bait_rod = if app_config.rod.bait_slots() > 0 { app_config.rod } else { Rod::IridiumRod };
+    let rod_suffix = if bait_rod == app_config.rod { String::new() } else { format!(" ({})", bait_rod.name()) };
+
+    let mut bait_fish_scenarios = Vec::new();
+    let mut handled_baits = HashSet::new();
+    for &item in segment_items {
+        // 只为 Fish.json 中定义的物品创建特制鱼饵场景，且每种物品只创建一次
+        if game_data.fish.contains_key(&item.display_id) && handled_baits.insert(item.display_id.clone()) {
+            let fish_data = &game_data.fish[&item.display_id];
+            if fish_data.is_algae() { continue; }
+            let fish_name_en = &fish_data.name;
+
+            let mut bait_config = app_config.clone();
+            bait_config.rod = bait_rod;
+            bait_config.bait_item_id = Some(Bait::Targeted.item_id().to_string());
+            bait_config.bait_target_fish_id = Some(item.display_id.clone());
+            bait_fish_scenarios.push((format!("{}{}", fish_name_en, rod_suffix), bait_config));
+        }
+    }
+
+    bait_fish_scenarios.sort_by_key(|(_name, cfg)| {
+        segment_items.iter().find(|item| &item.display_id == cfg.bait_target_fish_id.as_ref().unwrap())
+        .map_or(i32::MAX, |item| item.source_data.precedence)
+    });
+    scenarios.extend(bait_fish_scenarios);
+    scenarios
+}
+
+/// 将按概率排序的结果重新排列为“按优先级、同优先级保持解析顺序”的行顺序。
+fn sort_by_row_order(mut probabilities: Vec<ProbabilityDetails>, segment_items: &[&ResolvedItem]) -> Vec<ProbabilityDetails> {
+    let mut first_position: HashMap<&str, usize> = HashMap::new();
+    for (i, item) in segment_items.iter().enumerate() {
+        first_position.entry(item.display_id.as_str()).or_insert(i);
+    }
+    probabilities.sort_by_key(|p| (p.precedence, first_position.get(p.display_id.as_str()).cloned().unwrap_or(usize::MAX)));
+    probabilities
+}
@@ -0,0 +1,183 @@
+//! src/search.rs
+//!
+//! 反向查询：给定一条鱼，找出钓到它概率最高的地点、时间与鱼饵/钓具组合。
+//!
+//! 遍历 Locations.json 中的每个地点（Default 除外）、每个 FishArea、每个季节与天气，
+//! 以及每个时间段，对每种装备组合调用 `calculate_final_probabilities`，
+//! 并为每个时间段保留概率最高的组合。钓鱼等级、水深、职业等其余设置沿用用户配置。
+
+use crate::calculator;
+use crate::error::ConfigError;
+use crate::models::{AppConfig, Bait, GameData, ResolvedItem, Rod};
+use crate::utils;
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// 参与遍历的季节与天气，与 StringMap.json 映射后的内部值一致。
+pub const SEASONS: [&str; 4] = ["spring", "summer", "fall", "winter"];
+pub const WEATHERS: [&str; 2] = ["sunny", "rainy"];
+
+/// 一个地点、FishArea、季节、天气、时间段下的最佳结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub location: String,
+    pub fish_area: Option<String>,
+    pub season: String,
+    pub weather: String,
+    pub start_time: u32,
+    pub end_time: u32,
+    /// 最佳装备组合的名称，例如 "Specific Bait + Curiosity Lure"
+    pub setup: String,
+    pub bait_item_id: Option<String>,
+    pub has_curiosity_lure: bool,
+    pub is_training_rod: bool,
+    pub final_prob: f64,
+}
+
+/// 将鱼的名称（英文或 StringMap.json 中的中文）或物品 Id 解析为带 `(O)` 前缀的 Id。
+pub fn resolve_fish_id(query: &str, game_data: &GameData) -> Result<String, ConfigError> {
+    let query = query.trim();
+    let qualified = if query.starts_with('(') { query.to_string() } else { format!("(O){}", query) };
+    if game_data.fish.contains_key(&qualified) { return Ok(qualified); }
+
+    let name_en = game_data.string_map.fish_names.get(query).map_or(query, String::as_str);
+    game_data.fish_name_to_id.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(name_en))
+        .map(|(_, id)| id.clone())
+        .ok_or_else(|| ConfigError::UnknownFish(query.to_string()))
+}
+
+/// 遍历所有地点与装备组合，按概率从高到低返回钓到 `fish_id` 的结果。
+pub fn search_fish(fish_id: &str, config: &AppConfig, game_data: &GameData) -> Vec<SearchResult> {
+    let mut location_names: Vec<&String> = game_data.locations.keys()
+        .filter(|name| name.as_str() != "Default")
+        .collect();
+    location_names.sort();
+
+    let setups = build_setups(Some(fish_id), config, game_data);
+
+    let mut results: Vec<SearchResult> = location_names.par_iter().flat_map_iter(|&location_name| {
+        let mut location_results = Vec::new();
+        for season in SEASONS {
+            for weather in WEATHERS {
+                let mut base_config = config.clone();
+                base_config.location_name = location_name.clone();
+                base_config.season = season.to_string();
+                base_config.weather = weather.to_string();
+                base_config.game_state.weather_id = None;
+
+                for area_id in calculator::get_fish_area_ids(location_name, game_data) {
+                    location_results.extend(search_area(fish_id, &base_config, &setups, game_data, area_id));
+                }
+            }
+        }
+        location_results
+    }).collect();
+
+    results.sort_by(|a, b| b.final_prob.total_cmp(&a.final_prob));
+    results
+}
+
+/// 在一个 FishArea 中，为每个时间段选出概率最高的装备组合。
+fn search_area(
+    fish_id: &str,
+    base_config: &AppConfig,
+    setups: &[(String, AppConfig)],
+    game_data: &GameData,
+    area_id: Option<String>,
+) -> Vec<SearchResult> {
+    // 时间段 -> 当前最佳 (组合下标, 概率)
+    let mut best: Vec<((u32, u32), usize, f64)> = Vec::new();
+
+    for (setup_index, (_, setup)) in setups.iter().enumerate() {
+        let mut setup_config = base_config.clone();
+        setup_config.rod = setup.rod;
+        setup_config.bait_item_id = setup.bait_item_id.clone();
+        setup_config.bait_target_fish_id = setup.bait_target_fish_id.clone();
+        setup_config.tackles = setup.tackles.clone();
+
+        let base_items = calculator::get_resolved_fish_list(&setup_config, game_data, &area_id, false);
+        if !base_items.iter().any(|item| item.display_id == fish_id) { continue; }
+
+        for segment in utils::calculate_time_segments(&base_items, game_data) {
+            let segment_items = calculator::filter_items_for_time_segment(segment, &base_items, &setup_config, game_data);
+            if !segment_items.iter().any(|item| item.display_id == fish_id) { continue; }
+            let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+
+            let prob: f64 = calculator::calculate_final_probabilities(&segment_items, &setup_config, game_data)
+                .iter()
+                .filter(|p| p.display_id == fish_id)
+                .map(|p| p.final_prob)
+                .sum();
+            if prob <= 0.0 { continue; }
+
+            match best.iter_mut().find(|(s, _, _)| *s == segment) {
+                Some(entry) if entry.2 < prob => *entry = (segment, setup_index, prob),
+                Some(_) => {}
+                None => best.push((segment, setup_index, prob)),
+            }
+        }
+    }
+
+    best.sort_by_key(|(segment, _, _)| *segment);
+    best.into_iter().map(|((start_time, end_time), setup_index, final_prob)| {
+        let (setup_name, setup) = &setups[setup_index];
+        SearchResult {
+            location: base_config.location_name.clone(),
+            fish_area: area_id.clone(),
+            season: base_config.season.clone(),
+            weather: base_config.weather.clone(),
+            start_time,
+            end_time,
+            setup: setup_name.clone(),
+            bait_item_id: setup.bait_item_id.clone(),
+            has_curiosity_lure: setup.has_curiosity_lure(),
+            is_training_rod: setup.is_training_rod(),
+            final_prob,
+        }
+    }).collect()
+}
+
+/// 列出参与比较的装备组合：不用鱼饵、普通鱼饵、特制鱼饵（仅在指定目标鱼时）、魔法鱼饵以及用户配置的鱼饵，
+/// 各自搭配或不搭配珍稀诱钩；以及不能装备鱼饵和钓具的训练钓竿。
+/// 用户的钓竿缺少鱼饵或钓具槽位时，这些组合改用铱金钓竿，名称后附 " (Iridium Rod)"。
+pub(crate) fn build_setups(target_fish_id: Option<&str>, config: &AppConfig, game_data: &GameData) -> Vec<(String, AppConfig)> {
+    let mut baits = vec![None, Some(Bait::Regular)];
+    let specific_bait_target = target_fish_id.filter(|id| game_data.fish.get(*id).is_some_and(|fish| !fish.is_algae()));
+    if specific_bait_target.is_some() {
+        baits.push(Some(Bait::Targeted));
+    }
+    baits.push(Some(Bait::Magic));
+    // 用户配置的其他鱼饵（野生、豪华、挑战、磁铁）也参与比较
+    if let Some(bait) = config.bait().filter(|bait| !baits.contains(&Some(*bait)) && *bait != Bait::Targeted) {
+        baits.push(Some(bait));
+    }
+
+    // 钓竿装不下鱼饵或珍稀诱钩时，换成铱金钓竿，并在名称中注明
+    let rod = if config.rod.bait_slots() > 0 && config.rod.tackle_slots() > 0 { config.rod } else { Rod::IridiumRod };
+    let rod_suffix = if rod == config.rod { String::new() } else { format!(" ({})", rod.name()) };
+
+    let mut setups = Vec::new();
+    for bait in baits {
+        let bait_name = bait.map_or("No Bait", Bait::name);
+        for has_curiosity_lure in [false, true] {
+            let mut setup = config.clone();
+            setup.rod = rod;
+            setup.bait_item_id = bait.map(|bait| bait.item_id().to_string());
+            setup.bait_target_fish_id = specific_bait_target.filter(|_| bait == Some(Bait::Targeted)).map(str::to_string);
+            setup.set_curiosity_lure(has_curiosity_lure);
+
+            let lure_suffix = if has_curiosity_lure { " + Curiosity Lure" } else { "" };
+            setups.push((format!("{}{}{}", bait_name, lure_suffix, rod_suffix), setup));
+        }
+    }
+
+    let mut training_rod = config.clone();
+    training_rod.rod = Rod::TrainingRod;
+    training_rod.bait_item_id = None;
+    training_rod.bait_target_fish_id = None;
+    training_rod.tackles.clear();
+    setups.push(("Training Rod".to_string(), training_rod));
+
+    setups
+}
@@ -0,0 +1,264 @@
+//! src/simulation.rs
+//!
+//! 刷鱼循环的蒙特卡洛模拟，用于独立验证 calculator.rs 中的确定性序贯模型。
+//!
+//! 每次抛竿逐条复刻游戏中 GetFishFromLocationData 的循环：
+//! 按 Precedence 从小到大遍历，同一 Precedence 内随机打乱顺序，
+//! 对每个条目先判定 GetChance，再判定咬钩，第一个两者都成功的物品被钓到。
+//! 使用好鱼饵时，第一轮什么都没钓到会再遍历一轮；
+//! 使用特制鱼饵时，钓到的不是目标鱼会记下它并重新遍历，第二轮仍未钓到目标鱼时返回第一次记下的物品。
+//!
+//! 模拟结果按物品聚合为经验频率，并给出 Wilson 置信区间；
+//! `calculate_final_probabilities` 的结果落在区间之外时标记为偏离。
+
+use crate::calculator;
+use crate::models::{AppConfig, Bait, GameData, ResolvedItem};
+use crate::utils;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// 置信区间使用的 z 值（双侧 99.9%）。一个时间段内有几十个物品，取较宽的区间以免误报。
+pub const CONFIDENCE_Z: f64 = 3.29;
+
+/// 刷鱼循环中一个条目的判定参数。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpawnRoll {
+    pub precedence: i32,
+    pub get_chance: f64,
+    pub bite_chance: f64,
+    /// 是否为特制鱼饵的目标鱼
+    pub is_target: bool,
+}
+
+/// 一个物品的模拟结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedItem {
+    pub display_id: String,
+    pub name: String,
+    /// calculate_final_probabilities 给出的概率
+    pub exact_prob: f64,
+    /// 模拟得到的频率
+    pub observed_prob: f64,
+    pub catches: u64,
+    pub ci_low: f64,
+    pub ci_high: f64,
+    /// 精确概率是否落在置信区间之外
+    pub diverges: bool,
+}
+
+/// 一个地点、一个 FishArea、一个时间段的模拟结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentSimulation {
+    pub location: String,
+    pub fish_area: Option<String>,
+    pub start_time: u32,
+    pub end_time: u32,
+    pub casts: u64,
+    pub seed: u64,
+    /// 按精确概率从高到低排列，同一物品的多个条目合并为一行
+    pub items: Vec<SimulatedItem>,
+    /// 什么都没钓到的概率（游戏中会改为钓到垃圾）
+    pub nothing: SimulatedItem,
+    /// 偏离的行数，包括 nothing
+    pub divergent_count: usize,
+}
+
+/// 模拟 `casts` 次抛竿，返回每个条目被钓到的次数。相同的参数与种子总是得到相同的结果。
+///
+/// `passes` 为遍历轮数；`targeted` 为 true 时按特制鱼饵的规则处理非目标鱼。
+pub fn simulate_casts(rolls: &[SpawnRoll], passes: u32, targeted: bool, casts: u64, seed: u64) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // 按 Precedence 分组，每轮在组内原地打乱
+    let mut groups: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+    for (index, roll) in rolls.iter().enumerate() {
+        groups.entry(roll.precedence).or_default().push(index);
+    }
+    let mut groups: Vec<Vec<usize>> = groups.into_values().collect();
+
+    let mut counts = vec![0u64; rolls.len()];
+    for _ in 0..casts {
+        if let Some(index) = cast_once(rolls, &mut groups, passes, targeted, &mut rng) {
+            counts[index] += 1;
+        }
+    }
+    counts
+}
+
+/// 模拟一次抛竿，返回钓到的条目。
+fn cast_once(rolls: &[SpawnRoll], groups: &mut [Vec<usize>], passes: u32, targeted: bool, rng: &mut StdRng) -> Option<usize> {
+    let mut first_non_target = None;
+    for _ in 0..passes {
+        let mut caught = None;
+        'pass: for group in groups.iter_mut() {
+            group.shuffle(rng);
+            for &index in group.iter() {
+                let roll = &rolls[index];
+                if rng.gen::<f64>() < roll.get_chance && rng.gen::<f64>() < roll.bite_chance {
+                    caught = Some(index);
+                    break 'pass;
+                }
+            }
+        }
+
+        match caught {
+            Some(index) if targeted && !rolls[index].is_target => {
+                first_non_target.get_or_insert(index);
+            }
+            Some(index) => return Some(index),
+            None => {}
+        }
+    }
+    first_non_target
+}
+
+/// 二项分布比例的 Wilson 置信区间。
+pub fn wilson_interval(successes: u64, trials: u64, z: f64) -> (f64, f64) {
+    if trials == 0 { return (0.0, 1.0); }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half_width = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - half_width).max(0.0), (center + half_width).min(1.0))
+}
+
+/// 由用户的种子、FishArea 的下标与时间段派生每个时间段的种子。
+/// 使用固定的混合函数，同一种子在不同的 Rust 版本下得到相同的结果。
+fn segment_seed(seed: u64, area_index: usize, (start, end): (u32, u32)) -> u64 {
+    let area_mixed = utils::splitmix64(utils::splitmix64(seed) ^ area_index as u64);
+    utils::splitmix64(area_mixed ^ ((start as u64) << 32 | end as u64))
+}
+
+/// 对配置中地点的每个 FishArea、每个时间段运行模拟，只针对用户当前的配置。
+pub fn build_simulation_report(config: &AppConfig, game_data: &GameData, casts: u64, seed: u64) -> Vec<SegmentSimulation> {
+    let mut reports = Vec::new();
+
+    for (area_index, area_id) in calculator::get_fish_area_ids(&config.location_name, game_data).into_iter().enumerate() {
+        let base_items = calculator::get_resolved_fish_list(config, game_data, &area_id, false);
+
+        for segment in utils::calculate_time_segments(&base_items, game_data) {
+            if !utils::includes_configured_time(segment, config) { continue; }
+            let segment_items = calculator::filter_items_for_time_segment(segment, &base_items, config, game_data);
+            if segment_items.is_empty() { continue; }
+            let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+
+            let segment_seed = segment_seed(seed, area_index, segment);
+            let (items, nothing) = simulate_segment(&segment_items, config, game_data, casts, segment_seed);
+
+            reports.push(SegmentSimulation {
+                location: config.location_name.clone(),
+                fish_area: area_id.clone(),
+                start_time: segment.0,
+                end_time: segment.1,
+                casts,
+                seed,
+                divergent_count: items.iter().chain([&nothing]).filter(|item| item.diverges).count(),
+                items,
+                nothing,
+            });
+        }
+    }
+
+    reports
+}
+
+/// 模拟一个时间段，并与精确概率逐物品比较。
+fn simulate_segment(
+    items: &[&ResolvedItem],
+    config: &AppConfig,
+    game_data: &GameData,
+    casts: u64,
+    seed: u64,
+) -> (Vec<SimulatedItem>, SimulatedItem) {
+    let rolls: Vec<SpawnRoll> = items.iter().map(|&item| {
+        let (get_chance, bite_chance) = calculator::get_individual_success_rates(item, config, game_data);
+        SpawnRoll {
+            precedence: item.source_data.precedence,
+            get_chance,
+            bite_chance,
+            is_target: config.bait_target_fish_id.as_deref() == Some(&item.display_id),
+        }
+    }).collect();
+
+    let targeted = config.bait_target_fish_id.is_some();
+    let passes = if targeted { Bait::Targeted.spawn_passes() } else { config.bait().map_or(1, Bait::spawn_passes) };
+    let counts = simulate_casts(&rolls, passes as u32, targeted, casts, seed);
+
+    // 同一物品可能来自多个条目，按物品 Id 合并精确概率与钓到的次数
+    let mut merged: HashMap<String, (String, f64, u64)> = HashMap::new();
+    for p in calculator::calculate_final_probabilities(items, config, game_data) {
+        merged.entry(p.display_id).or_insert((p.name, 0.0, 0)).1 += p.final_prob;
+    }
+    for (&item, count) in items.iter().zip(&counts) {
+        if let Some(entry) = merged.get_mut(&item.display_id) {
+            entry.2 += count;
+        }
+    }
+
+    let mut results: Vec<SimulatedItem> = merged.into_iter()
+        .map(|(display_id, (name, exact_prob, catches))| compare(display_id, name, exact_prob, catches, casts))
+        .collect();
+    results.sort_by(|a, b| b.exact_prob.total_cmp(&a.exact_prob).then_with(|| a.display_id.cmp(&b.display_id)));
+
+    let nothing_exact = (1.0 - results.iter().map(|item| item.exact_prob).sum::<f64>()).max(0.0);
+    let nothing_catches = casts - counts.iter().sum::<u64>();
+    let nothing = compare(String::new(), "Nothing".to_string(), nothing_exact, nothing_catches, casts);
+
+    (results, nothing)
+}
+
+fn compare(display_id: String, name: String, exact_prob: f64, catches: u64, casts: u64) -> SimulatedItem {
+    let (ci_low, ci_high) = wilson_interval(catches, casts, CONFIDENCE_Z);
+    // 留出浮点误差的余量，避免精确为 0 或 1 的概率被误判
+    let diverges = exact_prob < ci_low - 1e-12 || exact_prob > ci_high + 1e-12;
+    SimulatedItem {
+        display_id,
+        name,
+        exact_prob,
+        observed_prob: if casts > 0 { catches as f64 / casts as f64 } else { 0.0 },
+        catches,
+        ci_low,
+        ci_high,
+        diverges,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roll(precedence: i32, chance: f64) -> SpawnRoll {
+        SpawnRoll { precedence, get_chance: chance, bite_chance: 1.0, is_target: false }
+    }
+
+    #[test]
+    fn single_group_matches_exact_model() {
+        let rates = [0.6, 0.3, 0.15, 0.05];
+        let rolls: Vec<SpawnRoll> = rates.iter().map(|&rate| roll(0, rate)).collect();
+        let casts = 200_000;
+        let counts = simulate_casts(&rolls, 1, false, casts, 7);
+
+        for (count, exact) in counts.iter().zip(calculator::group_first_success_probabilities(&rates)) {
+            let (low, high) = wilson_interval(*count, casts, CONFIDENCE_Z);
+            assert!(low <= exact && exact <= high, "exact {} outside [{}, {}]", exact, low, high);
+        }
+    }
+
+    #[test]
+    fn segment_seeds_are_fixed_and_distinct() {
+        assert_eq!(segment_seed(42, 0, (600, 1200)), segment_seed(42, 0, (600, 1200)));
+        assert_ne!(segment_seed(42, 0, (600, 1200)), segment_seed(42, 1, (600, 1200)));
+        assert_ne!(segment_seed(42, 0, (600, 1200)), segment_seed(42, 0, (1200, 600)));
+        assert_ne!(segment_seed(42, 0, (600, 1200)), segment_seed(43, 0, (600, 1200)));
+    }
+
+    #[test]
+    fn same_seed_gives_same_counts() {
+        let rolls = [roll(-1, 0.2), roll(0, 0.5), roll(0, 0.5)];
+        assert_eq!(simulate_casts(&rolls, 2, false, 1_000, 3), simulate_casts(&rolls, 2, false, 1_000, 3));
+    }
+}
@@ -0,0 +1,186 @@
+//! src/summary.rs
+//!
+//! 将各时间段的结果按时长加权汇总为全天、全季的期望概率。
+//!
+//! 一个时间段的权重为其包含的 10 分钟刻数。汇总只针对用户当前的配置（钓竿、鱼饵等），
+//! 不包含对比模式中的其他场景。全季汇总对 28 天中的每一天、每种天气分别计算全天结果，
+//! 再按天气出现的概率加权平均。
+
+use crate::calculator;
+use crate::models::{AppConfig, GameData, ResolvedItem};
+use crate::utils;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 一个季节的天数。
+pub const DAYS_PER_SEASON: u32 = 28;
+
+/// 有节日的日期 (季节, 日期)。节日当天固定为晴天。
+pub const FESTIVAL_DAYS: [(&str, u32); 8] = [
+    ("spring", 13), ("spring", 24),
+    ("summer", 11), ("summer", 28),
+    ("fall", 16), ("fall", 27),
+    ("winter", 8), ("winter", 25),
+];
+
+/// 汇总的范围。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryScope {
+    Day,
+    Season,
+}
+
+/// 单个物品的汇总结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemSummary {
+    pub display_id: String,
+    pub name: String,
+    pub precedence: i32,
+    pub source_group_id: String,
+    /// 在汇总范围内随机一次抛竿钓到该物品的期望概率
+    pub mean_prob: f64,
+    /// 在所有钓到的物品中，该物品所占的期望比例
+    pub catch_share: f64,
+}
+
+/// 一个地点、一个 FishArea 的汇总结果。
+#[derive(Debug, Clone, Serialize)]
+pub struct AreaSummary {
+    pub location: String,
+    pub fish_area: Option<String>,
+    pub scope: SummaryScope,
+    pub season: String,
+    /// 全天汇总时为配置中的天气；全季汇总时为 `None`
+    pub weather: Option<String>,
+    /// 一次抛竿钓到任意物品的期望概率
+    pub catch_prob: f64,
+    /// 按 mean_prob 从高到低排列
+    pub items: Vec<ItemSummary>,
+}
+
+/// 汇总过程中的累加器：display_id -> (名称, 优先级, source_group_id, 加权概率之和)。
+type ItemAccumulator = HashMap<String, (String, i32, String, f64)>;
+
+/// 按时段时长加权，汇总配置中地点每个 FishArea 的全天结果。
+pub fn summarize_day(config: &AppConfig, game_data: &GameData) -> Vec<AreaSummary> {
+    calculator::get_fish_area_ids(&config.location_name, game_data).into_iter().map(|area_id| {
+        let accumulator = accumulate_day(config, game_data, &area_id, 1.0);
+        finish_summary(config, area_id, SummaryScope::Day, Some(config.weather.clone()), accumulator)
+    }).filter(|summary| !summary.items.is_empty()).collect()
+}
+
+/// 汇总配置中地点每个 FishArea 在整个季节、所有天气下的结果。
+pub fn summarize_season(config: &AppConfig, game_data: &GameData) -> Vec<AreaSummary> {
+    calculator::get_fish_area_ids(&config.location_name, game_data).into_iter().map(|area_id| {
+        let mut accumulator = ItemAccumulator::new();
+        for day in 1..=DAYS_PER_SEASON {
+            let rain_chance = daily_rain_chance(&config.season, day);
+            for (weather, weather_chance) in [("sunny", 1.0 - rain_chance), ("rainy", rain_chance)] {
+                if weather_chance <= 0.0 { continue; }
+
+                let mut day_config = config.clone();
+                day_config.weather = weather.to_string();
+                day_config.game_state.day_of_month = day;
+                day_config.game_state.weather_id = None;
+                day_config.game_state.is_festival_day = is_festival_day(&config.season, day);
+
+                let weight = weather_chance / DAYS_PER_SEASON as f64;
+                for (id, (name, precedence, group_id, prob)) in accumulate_day(&day_config, game_data, &area_id, weight) {
+                    let entry = accumulator.entry(id).or_insert((name, precedence, group_id, 0.0));
+                    entry.1 = entry.1.min(precedence);
+                    entry.3 += prob;
+                }
+            }
+        }
+        finish_summary(config, area_id, SummaryScope::Season, None, accumulator)
+    }).filter(|summary| !summary.items.is_empty()).collect()
+}
+
+/// 某季节某一天是否有节日。
+pub fn is_festival_day(season: &str, day_of_month: u32) -> bool {
+    FESTIVAL_DAYS.contains(&(season, day_of_month))
+}
+
+/// 某季节某一天下雨（含雷雨）的概率，参照游戏中的天气生成规则。
+/// 每季第一天与节日固定为晴天，夏季 13、26 日固定为雷雨；冬季下雪不算下雨。
+/// 不考虑第一年的剧情天气与 1.6 的绿雨。
+pub fn daily_rain_chance(season: &str, day_of_month: u32) -> f64 {
+    if day_of_month == 1 || is_festival_day(season, day_of_month) { return 0.0; }
+    if season == "summer" && (day_of_month == 13 || day_of_month == 26) { return 1.0; }
+    match season {
+        "spring" | "fall" => 0.183,
+        "summer" => 0.12 + 0.003 * day_of_month as f64,
+        _ => 0.0,
+    }
+}
+
+/// 计算一天中每个物品按时段时长加权的平均概率，并乘以 `weight` 返回。
+fn accumulate_day(config: &AppConfig, game_data: &GameData, area_id: &Option<String>, weight: f64) -> ItemAccumulator {
+    let mut accumulator = ItemAccumulator::new();
+
+    let base_items = calculator::get_resolved_fish_list(config, game_data, area_id, false);
+    let time_segments = utils::calculate_time_segments(&base_items, game_data);
+    let total_ticks: u32 = time_segments.iter().map(|&segment| utils::segment_ticks(segment)).sum();
+    if total_ticks == 0 { return accumulator; }
+
+    for segment in time_segments {
+        let segment_items = calculator::filter_items_for_time_segment(segment, &base_items, config, game_data);
+        let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+        let segment_weight = weight * utils::segment_ticks(segment) as f64 / total_ticks as f64;
+
+        for p in calculator::calculate_final_probabilities(&segment_items, config, game_data) {
+            let entry = accumulator.entry(p.display_id).or_insert((p.name, p.precedence, p.source_group_id, 0.0));
+            entry.1 = entry.1.min(p.precedence);
+            entry.3 += segment_weight * p.final_prob;
+        }
+    }
+    accumulator
+}
+
+fn finish_summary(
+    config: &AppConfig,
+    fish_area: Option<String>,
+    scope: SummaryScope,
+    weather: Option<String>,
+    accumulator: ItemAccumulator,
+) -> AreaSummary {
+    let catch_prob = accumulator.values().fold(0.0, |sum, (_, _, _, prob)| sum + prob);
+    let mut items: Vec<ItemSummary> = accumulator.into_iter().map(|(display_id, (name, precedence, source_group_id, mean_prob))| {
+        ItemSummary {
+            display_id,
+            name,
+            precedence,
+            source_group_id,
+            mean_prob,
+            catch_share: if catch_prob > 0.0 { mean_prob / catch_prob } else { 0.0 },
+        }
+    }).collect();
+    items.sort_by(|a, b| b.mean_prob.total_cmp(&a.mean_prob).then_with(|| a.display_id.cmp(&b.display_id)));
+
+    AreaSummary {
+        location: config.location_name.clone(),
+        fish_area,
+        scope,
+        season: config.season.clone(),
+        weather,
+        catch_prob,
+        items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_weather_days_have_fixed_rain_chance() {
+        assert_eq!(daily_rain_chance("spring", 1), 0.0);
+        // 复活节
+        assert_eq!(daily_rain_chance("spring", 13), 0.0);
+        assert_eq!(daily_rain_chance("spring", 12), 0.183);
+        assert!((daily_rain_chance("summer", 20) - 0.18).abs() < 1e-12);
+        assert_eq!(daily_rain_chance("summer", 13), 1.0);
+        assert_eq!(daily_rain_chance("winter", 20), 0.0);
+    }
+}
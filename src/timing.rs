@@ -0,0 +1,161 @@
+//! src/timing.rs
+//!
+//! 将每次抛竿的概率换算为期望抛竿次数与期望耗时。
+//!
+//! 每次抛竿的耗时 = 抛竿时间 + 平均等待咬钩时间 + 收线时间，
+//! 其中收线时间取决于钓到的物品：Fish.json 中的鱼需要进行小游戏，其他物品直接收线。
+//! 各次抛竿相互独立，因此钓到目标所需的抛竿次数服从几何分布，期望为 `1 / final_prob`；
+//! 由 Wald 等式，期望耗时为 `每次抛竿的期望耗时 / final_prob`。
+//! 估算假设整个过程都处在同一个时间段内，不考虑中途跨越时间段带来的概率变化。
+
+use crate::calculator;
+use crate::models::{AppConfig, GameData, ProbabilityDetails, ResolvedItem};
+use crate::utils;
+use serde::Serialize;
+
+/// 单个物品在一个时间段内的期望耗时估算。
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemTiming {
+    pub display_id: String,
+    pub name: String,
+    pub precedence: i32,
+    pub source_group_id: String,
+    pub final_prob: f64,
+    /// 钓到第一个该物品的期望抛竿次数；概率为 0 时为 `None`
+    pub expected_casts: Option<f64>,
+    /// 钓到第一个该物品的期望游戏内分钟数；概率为 0 时为 `None`
+    pub expected_game_minutes: Option<f64>,
+    /// 整个时间段内持续钓鱼时，期望钓到的数量（计入野生鱼饵、挑战鱼饵的额外数量）
+    pub expected_catches: f64,
+}
+
+/// 一个地点、一个 FishArea、一个时间段的耗时估算。
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentTiming {
+    pub location: String,
+    pub fish_area: Option<String>,
+    pub start_time: u32,
+    pub end_time: u32,
+    /// 每次抛竿的期望现实秒数
+    pub seconds_per_cast: f64,
+    /// 每次抛竿的期望游戏内分钟数
+    pub game_minutes_per_cast: f64,
+    /// 整个时间段内的期望抛竿次数
+    pub casts_per_segment: f64,
+    /// 按 final_prob 从高到低排列
+    pub items: Vec<ItemTiming>,
+}
+
+/// 为配置中地点的每个 FishArea、每个时间段计算耗时估算，只针对用户当前的配置。
+pub fn build_timing_report(config: &AppConfig, game_data: &GameData) -> Vec<SegmentTiming> {
+    let mut reports = Vec::new();
+
+    for area_id in calculator::get_fish_area_ids(&config.location_name, game_data) {
+        let base_items = calculator::get_resolved_fish_list(config, game_data, &area_id, false);
+
+        for segment in utils::calculate_time_segments(&base_items, game_data) {
+            if !utils::includes_configured_time(segment, config) { continue; }
+            let segment_items = calculator::filter_items_for_time_segment(segment, &base_items, config, game_data);
+            if segment_items.is_empty() { continue; }
+            let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+
+            let probabilities = calculator::calculate_final_probabilities(&segment_items, config, game_data);
+            reports.push(build_segment_timing(config, game_data, area_id.clone(), segment, probabilities));
+        }
+    }
+
+    reports
+}
+
+/// 由一个时间段的最终概率计算期望抛竿次数、耗时与钓获数量。
+pub fn build_segment_timing(
+    config: &AppConfig,
+    game_data: &GameData,
+    fish_area: Option<String>,
+    (start_time, end_time): (u32, u32),
+    probabilities: Vec<ProbabilityDetails>,
+) -> SegmentTiming {
+    let model = &config.cast_timing;
+    let seconds_per_cast = expected_seconds_per_cast(&probabilities, config, game_data);
+    let game_minutes_per_cast = seconds_per_cast / model.seconds_per_game_minute;
+    let segment_seconds = utils::segment_ticks((start_time, end_time)) as f64 * 10.0 * model.seconds_per_game_minute;
+    let casts_per_segment = if seconds_per_cast > 0.0 { segment_seconds / seconds_per_cast } else { 0.0 };
+
+    let items = probabilities.into_iter().map(|p| {
+        let expected_casts = (p.final_prob > 0.0).then(|| 1.0 / p.final_prob);
+        ItemTiming {
+            expected_game_minutes: expected_casts.map(|casts| casts * game_minutes_per_cast),
+            expected_casts,
+            expected_catches: casts_per_segment * p.final_prob * p.expected_count,
+            display_id: p.display_id,
+            name: p.name,
+            precedence: p.precedence,
+            source_group_id: p.source_group_id,
+            final_prob: p.final_prob,
+        }
+    }).collect();
+
+    SegmentTiming {
+        location: config.location_name.clone(),
+        fish_area,
+        start_time,
+        end_time,
+        seconds_per_cast,
+        game_minutes_per_cast,
+        casts_per_segment,
+        items,
+    }
+}
+
+/// 一次抛竿的期望现实秒数。概率之和不足 1 的部分视为没有小游戏的物品。
+pub fn expected_seconds_per_cast(probabilities: &[ProbabilityDetails], config: &AppConfig, game_data: &GameData) -> f64 {
+    let model = &config.cast_timing;
+    let p_minigame: f64 = probabilities.iter()
+        .filter(|p| game_data.fish.get(&p.display_id).is_some_and(|fish| !fish.is_algae()))
+        .map(|p| p.final_prob)
+        .sum::<f64>()
+        .clamp(0.0, 1.0);
+
+    model.cast_seconds
+        + mean_bite_wait_seconds(config)
+        + p_minigame * model.minigame_seconds
+        + (1.0 - p_minigame) * model.reel_seconds
+}
+
+/// 平均等待咬钩时间：在 [最小值, 最大值] 上均匀分布的均值，使用鱼饵时再乘以倍率，
+/// 豪华鱼饵还会额外缩短。钓鱼等级与旋转器降低的是最大值。
+pub fn mean_bite_wait_seconds(config: &AppConfig) -> f64 {
+    let model = &config.cast_timing;
+    let tackle_reduction: f64 = config.tackles.iter().map(|t| t.bite_wait_reduction_seconds()).sum();
+    let max = (model.bite_wait_max_seconds - model.bite_wait_reduction_per_level * config.fishing_level as f64 - tackle_reduction)
+        .max(model.bite_wait_min_seconds);
+    let mean = (model.bite_wait_min_seconds + max) / 2.0;
+    match config.bait() {
+        Some(bait) => mean * model.bait_bite_wait_multiplier * bait.bite_wait_factor(),
+        None => mean,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Bait, Tackle};
+
+    #[test]
+    fn bite_wait_follows_level_bait_and_tackle() {
+        let mut config = AppConfig::for_test();
+        // 钓鱼等级 10：最长等待 30 - 0.25 × 10 = 27.5 秒，均值 (0.6 + 27.5) / 2
+        assert!((mean_bite_wait_seconds(&config) - 14.05).abs() < 1e-12);
+
+        config.bait_item_id = Some(Bait::Regular.item_id().to_string());
+        assert!((mean_bite_wait_seconds(&config) - 14.05 * 0.5).abs() < 1e-12);
+
+        config.bait_item_id = Some(Bait::Deluxe.item_id().to_string());
+        config.tackles = vec![Tackle::Spinner];
+        assert!((mean_bite_wait_seconds(&config) - (0.6 + 22.5) / 2.0 * 0.5 * 0.66).abs() < 1e-12);
+
+        // 最长等待时间不会低于最短等待时间
+        config.cast_timing.bite_wait_max_seconds = 1.0;
+        assert!((mean_bite_wait_seconds(&config) - 0.6 * 0.5 * 0.66).abs() < 1e-12);
+    }
+}
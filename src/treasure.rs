@@ -0,0 +1,182 @@
+//! src/treasure.rs
+//!
+//! 宝箱：钓到鱼时附带宝箱的概率，以及宝箱中战利品的期望数量。
+//!
+//! 宝箱概率与游戏一致：基础 15%，加上 运气等级（含运气增益）× 0.5%、每日运气 / 2，
+//! 磁铁 +15%、每个寻宝者 +5%、海盗职业 +15%。只有进入小游戏的鱼会附带宝箱，
+//! 这里给出的是宝箱出现的概率，不考虑小游戏中能否把宝箱钓上来；
+//! 并假设玩家已经钓到过至少两种鱼（游戏中出现宝箱的前提）。
+//! 解锁钓鱼精通后，宝箱有 25% + 每日运气 的概率成为金色宝箱，战利品更多。
+//!
+//! 战利品来自 data/TreasureLoot.json。游戏中逐项判定的开箱逻辑在这里近似为“等概率选类别、类别内按权重抽取”，
+//! 因此战利品分布只是估算；钓鱼等级与水深决定哪些物品可以出现。
+
+use crate::error::ConfigError;
+use crate::models::{AppConfig, GameData, ProbabilityDetails, TreasureLootData};
+use serde::Serialize;
+
+/// 宝箱的基础概率。
+pub const BASE_TREASURE_CHANCE: f64 = 0.15;
+
+/// 一个场景下的宝箱概率。
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TreasureOdds {
+    /// 钓到一条有小游戏的鱼时出现宝箱的概率
+    pub per_catch: f64,
+    /// 出现的宝箱是金色宝箱的概率
+    pub golden_share: f64,
+    /// 每次抛竿出现宝箱的概率
+    pub per_cast: f64,
+    /// 每次抛竿出现金色宝箱的概率
+    pub golden_per_cast: f64,
+}
+
+/// 一种战利品的期望数量。
+#[derive(Debug, Clone, Serialize)]
+pub struct LootExpectation {
+    pub item_id: String,
+    pub name: String,
+    pub category: String,
+    /// 普通宝箱中的期望数量
+    pub expected_quantity: f64,
+    /// 金色宝箱中的期望数量
+    pub golden_expected_quantity: f64,
+}
+
+/// 宝箱中战利品的分布。
+#[derive(Debug, Clone, Serialize)]
+pub struct TreasureLoot {
+    /// 普通宝箱的期望抽取次数
+    pub expected_rolls: f64,
+    /// 金色宝箱的期望抽取次数
+    pub golden_expected_rolls: f64,
+    /// 按普通宝箱中的期望数量从高到低排列
+    pub items: Vec<LootExpectation>,
+}
+
+/// 钓到一条有小游戏的鱼时出现宝箱的概率。
+pub fn treasure_chance(config: &AppConfig) -> f64 {
+    let bait_bonus = config.bait().map_or(0.0, |bait| bait.treasure_chance_bonus());
+    let tackle_bonus: f64 = config.tackles.iter().map(|t| t.treasure_chance_bonus()).sum();
+    let pirate_bonus = if config.professions.contains("Pirate") { BASE_TREASURE_CHANCE } else { 0.0 };
+
+    (BASE_TREASURE_CHANCE
+        + config.luck_level as f64 * 0.005
+        + config.daily_luck / 2.0
+        + bait_bonus
+        + tackle_bonus
+        + pirate_bonus)
+        .clamp(0.0, 1.0)
+}
+
+/// 宝箱成为金色宝箱的概率。未解锁钓鱼精通时为 0。
+pub fn golden_treasure_share(config: &AppConfig) -> f64 {
+    if config.has_fishing_mastery { (0.25 + config.daily_luck).clamp(0.0, 1.0) } else { 0.0 }
+}
+
+/// 由一个场景的最终概率计算宝箱概率。
+pub fn treasure_odds(probabilities: &[ProbabilityDetails], config: &AppConfig, game_data: &GameData) -> TreasureOdds {
+    let per_catch = treasure_chance(config);
+    let golden_share = golden_treasure_share(config);
+    let p_minigame: f64 = probabilities.iter()
+        .filter(|p| game_data.fish.get(&p.display_id).is_some_and(|fish| !fish.is_algae()))
+        .map(|p| p.final_prob)
+        .sum();
+
+    TreasureOdds {
+        per_catch,
+        golden_share,
+        per_cast: p_minigame * per_catch,
+        golden_per_cast: p_minigame * per_catch * golden_share,
+    }
+}
+
+/// 检查战利品表中继续抽取的概率。概率达到 1 时宝箱会无限抽取下去，期望抽取次数不存在。
+pub fn validate_loot(loot: &TreasureLootData) -> Result<(), ConfigError> {
+    for (field, value) in [("RollChance", loot.roll_chance), ("GoldenRollChance", loot.golden_roll_chance)] {
+        if !(0.0..1.0).contains(&value) {
+            return Err(ConfigError::InvalidRollChance { field: field.to_string(), value });
+        }
+    }
+    Ok(())
+}
+
+/// 期望抽取次数：第 k 次抽取发生的概率为 roll_chance^(1 + 2 + … + (k - 1))。
+/// `roll_chance` 需在 [0, 1) 之间，见 `validate_loot`。
+pub fn expected_rolls(roll_chance: f64) -> f64 {
+    let mut total = 0.0;
+    let mut p_roll = 1.0;
+    let mut continue_chance = 1.0;
+    while p_roll > 1e-12 {
+        total += p_roll;
+        continue_chance *= roll_chance;
+        p_roll *= continue_chance;
+    }
+    total
+}
+
+/// 按当前的水深与钓鱼等级计算宝箱中各物品的期望数量。没有 TreasureLoot.json 时返回 `None`。
+pub fn expected_loot(config: &AppConfig, game_data: &GameData) -> Option<TreasureLoot> {
+    let loot: &TreasureLootData = game_data.treasure_loot.as_ref()?;
+    let expected_rolls_normal = expected_rolls(loot.roll_chance);
+    let expected_rolls_golden = expected_rolls(loot.golden_roll_chance);
+    let category_prob = 1.0 / loot.categories.len().max(1) as f64;
+
+    let mut items = Vec::new();
+    for category in &loot.categories {
+        let eligible: Vec<_> = category.items.iter()
+            .filter(|item| config.water_depth >= item.min_water_depth && config.fishing_level >= item.min_fishing_level)
+            .collect();
+        let total_weight: f64 = eligible.iter().map(|item| item.weight).sum();
+        if total_weight <= 0.0 { continue; }
+
+        for item in eligible {
+            let mean_stack = (item.min_stack + item.max_stack) as f64 / 2.0;
+            let per_roll = category_prob * item.weight / total_weight * mean_stack;
+            items.push(LootExpectation {
+                item_id: item.item_id.clone(),
+                name: item.name.clone(),
+                category: category.id.clone(),
+                expected_quantity: per_roll * expected_rolls_normal,
+                golden_expected_quantity: per_roll * expected_rolls_golden,
+            });
+        }
+    }
+    items.sort_by(|a, b| b.expected_quantity.total_cmp(&a.expected_quantity));
+
+    Some(TreasureLoot {
+        expected_rolls: expected_rolls_normal,
+        golden_expected_rolls: expected_rolls_golden,
+        items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_rolls_sums_triangular_powers() {
+        assert_eq!(expected_rolls(0.0), 1.0);
+        let c: f64 = 0.4;
+        let expected = 1.0 + c + c.powi(3) + c.powi(6) + c.powi(10) + c.powi(15);
+        assert!((expected_rolls(c) - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn roll_chances_must_stay_below_one() {
+        let loot = |roll_chance, golden_roll_chance| TreasureLootData { roll_chance, golden_roll_chance, categories: vec![] };
+
+        assert_eq!(validate_loot(&loot(0.99, 0.99)), Ok(()));
+        assert!(expected_rolls(0.99).is_finite() && expected_rolls(0.99) > expected_rolls(0.4));
+        assert_eq!(
+            validate_loot(&loot(1.0, 0.5)),
+            Err(ConfigError::InvalidRollChance { field: "RollChance".to_string(), value: 1.0 })
+        );
+        assert_eq!(
+            validate_loot(&loot(0.4, 1.0)),
+            Err(ConfigError::InvalidRollChance { field: "GoldenRollChance".to_string(), value: 1.0 })
+        );
+        assert!(validate_loot(&loot(-0.1, 0.5)).is_err());
+    }
+}
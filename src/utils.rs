@@ -3,13 +3,14 @@
 //! 存放可复用的、无状态的工具函数。
 //! 遵循模块化原则，保持其他模块的逻辑清晰。
 
-use crate::models::{AppConfig, GameData, ResolvedItem};
+use crate::gsq;
+use crate::models::{AppConfig, GameData, QuantityModifier, QuantityModifierMode, ResolvedItem};
 use std::collections::BTreeSet;
 
 /// 解析LOCATION_FISH查询，如果成功则返回目标地点名称。
 pub fn parse_location_query(item_id: &str) -> Option<&str> {
     let parts: Vec<&str> = item_id.split_whitespace().collect();
-    if parts.get(0) == Some(&"LOCATION_FISH") {
+    if parts.first() == Some(&"LOCATION_FISH") {
         return parts.get(1).cloned();
     }
     None
@@ -27,6 +28,11 @@ pub fn calculate_time_segments(
     time_points.insert(2600);
 
     for item in resolved_items {
+        // Condition 中的 TIME 等查询同样会划分时间段，与 IgnoreFishDataRequirements 无关
+        for condition in &item.conditions {
+            time_points.extend(gsq::time_boundaries(condition).into_iter().filter(|t| (600..2600).contains(t)));
+        }
+
         // --- 核心修正：在这里检查 IgnoreFishDataRequirements 标志 ---
         // 如果此条目忽略 Fish.json 的要求，则它的时间窗口对我们没有意义，直接跳过。
         if item.source_data.ignore_fish_data_requirements {
@@ -50,54 +56,97 @@ pub fn calculate_time_segments(
 }
 
 
-/// 通用的 Condition 检查器。
-pub fn check_condition(condition_str: &Option<String>, config: &AppConfig) -> bool {
-    let Some(conditions) = condition_str else { return true; };
-    for query in conditions.split(',') {
-        if !evaluate_query(query.trim(), config) {
-            return false;
+/// 配置中指定了当前时间 (game_state.time_of_day) 时，时间段是否包含该时间；未指定时总是为 true。
+pub fn includes_configured_time((start, end): (u32, u32), config: &AppConfig) -> bool {
+    config.game_state.time_of_day.is_none_or(|time| start <= time && time < end)
+}
+
+/// 将 HHMM 格式的时间转换为从 0:00 起的分钟数。
+pub fn time_to_minutes(time: u32) -> u32 {
+    time / 100 * 60 + time % 100
+}
+
+/// 一个时间段包含的 10 分钟刻数。
+pub fn segment_ticks((start, end): (u32, u32)) -> u32 {
+    time_to_minutes(end).saturating_sub(time_to_minutes(start)) / 10
+}
+
+/// 按游戏中 Utility.ApplyQuantityModifiers 的规则，对数值应用一组数量修正器。
+/// 带 RandomAmount 的修正器取其期望值。
+///
+/// 修正器的条件可能带有随机性 (RANDOM)，因此返回所有可能结果及其概率 `(概率, 数值)`，
+/// 调用方可在截断 (clamp) 等非线性处理之后再求期望。
+///
+/// 修正器逐个折叠进结果分布，数值相同的结果随即合并，
+/// 因此不确定的修正器再多也不会按 2^n 展开。
+pub fn apply_quantity_modifiers(
+    value: f64,
+    modifiers: &[QuantityModifier],
+    mode: QuantityModifierMode,
+    config: &AppConfig,
+) -> Vec<(f64, f64)> {
+    // 每个结果记录 (概率, 目前为止的数值)；还没有修正器生效时数值为 None
+    let mut outcomes: Vec<(f64, Option<f64>)> = vec![(1.0, None)];
+
+    for modifier in modifiers {
+        let weight = gsq::check_condition(&modifier.condition, config);
+        if weight <= 0.0 { continue; }
+        let amount = match &modifier.random_amount {
+            Some(amounts) if !amounts.is_empty() => amounts.iter().sum::<f64>() / amounts.len() as f64,
+            _ => modifier.amount,
+        };
+        let apply = |current: Option<f64>| Some(match mode {
+            QuantityModifierMode::Stack => modifier.modification.apply(current.unwrap_or(value), amount),
+            QuantityModifierMode::Minimum => {
+                let applied = modifier.modification.apply(value, amount);
+                current.map_or(applied, |v| v.min(applied))
+            }
+            QuantityModifierMode::Maximum => {
+                let applied = modifier.modification.apply(value, amount);
+                current.map_or(applied, |v| v.max(applied))
+            }
+        });
+
+        if weight >= 1.0 {
+            outcomes.iter_mut().for_each(|(_, current)| *current = apply(*current));
+        } else {
+            outcomes = outcomes.into_iter()
+                .flat_map(|(p, current)| [(p * weight, apply(current)), (p * (1.0 - weight), current)])
+                .collect();
+            merge_outcomes(&mut outcomes);
         }
     }
-    true
+
+    outcomes.into_iter().map(|(p, current)| (p, current.unwrap_or(value))).collect()
+}
+
+/// 合并数值相同的结果。
+fn merge_outcomes(outcomes: &mut Vec<(f64, Option<f64>)>) {
+    outcomes.sort_by(|(_, a), (_, b)| match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(b),
+        _ => a.is_some().cmp(&b.is_some()),
+    });
+    outcomes.dedup_by(|(p, a), (kept_p, b)| {
+        let same = a.map(f64::to_bits) == b.map(f64::to_bits);
+        if same { *kept_p += *p; }
+        same
+    });
 }
 
-fn evaluate_query(query: &str, config: &AppConfig) -> bool {
-    let (is_negated, trimmed_query) = if let Some(q) = query.strip_prefix('!') {
-        (true, q)
-    } else {
-        (false, query)
-    };
-
-    let parts: Vec<&str> = trimmed_query.split_whitespace().collect();
-    let Some(&key) = parts.get(0) else { return true; };
-    let args = &parts[1..];
-
-    // --- 查询调度中心 ---
-    let result = match key {
-        "LOCATION_SEASON" => {
-            if args.get(0) == Some(&"Here") {
-                let valid_seasons = &args[1..];
-                // --- 修正：使用 .any() 来检查是否包含当前季节 ---
-                valid_seasons.iter().any(|&s| s == config.season.as_str())
-            } else { false }
-        },
-        "PLAYER_SPECIAL_ORDER_RULE_ACTIVE" => {
-            if args.len() == 2 && args[0] == "Current" {
-                let required_order_id = args[1];
-                // --- 修正：精确匹配 config 中设置的规则 ID ---
-                 config.conditions.get("PLAYER_SPECIAL_ORDER_RULE_ACTIVE Current")
-                    .map_or(false, |active_rule| active_rule == required_order_id)
-            } else { false }
-        },
-        _ => {
-            // 默认行为：检查条件是否存在且为 "true"
-            config.conditions.get(trimmed_query).map_or(false, |v| v == "true")
-        }
-    };
 
-    if is_negated { !result } else { result }
+/// SplitMix64 的输出函数，用于由用户的种子派生子种子。
+/// 与 `std::hash::DefaultHasher` 不同，结果不随 Rust 版本变化。
+pub fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
+/// 字符串的 64 位 FNV-1a 哈希，同样不随 Rust 版本变化。
+pub fn stable_hash(s: &str) -> u64 {
+    s.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3))
+}
 
 /// 将字符串截断到指定的最大宽度，如果发生截断则添加"..."
 pub fn truncate_string(s: &str, max_width: usize) -> String {
@@ -110,4 +159,65 @@ pub fn truncate_string(s: &str, max_width: usize) -> String {
     }
     
     format!("{}...", s.chars().take(max_width - 3).collect::<String>())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModificationType;
+
+    fn modifier(modification: ModificationType, amount: f64, condition: Option<&str>) -> QuantityModifier {
+        QuantityModifier { id: None, condition: condition.map(str::to_string), modification, amount, random_amount: None }
+    }
+
+    fn sorted(mut outcomes: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+        outcomes.sort_by(|a, b| a.1.total_cmp(&b.1));
+        outcomes
+    }
+
+    fn assert_outcomes(actual: Vec<(f64, f64)>, expected: &[(f64, f64)]) {
+        let actual = sorted(actual);
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for ((p, v), (ep, ev)) in actual.iter().zip(expected) {
+            assert!((p - ep).abs() < 1e-12 && (v - ev).abs() < 1e-12, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn one_uncertain_modifier_in_each_mode() {
+        let config = AppConfig::for_test();
+        let modifiers = [
+            modifier(ModificationType::Multiply, 2.0, None),
+            modifier(ModificationType::Add, 0.1, Some("RANDOM 0.25")),
+        ];
+
+        let stack = apply_quantity_modifiers(0.5, &modifiers, QuantityModifierMode::Stack, &config);
+        assert_outcomes(stack, &[(0.75, 1.0), (0.25, 1.1)]);
+        let minimum = apply_quantity_modifiers(0.5, &modifiers, QuantityModifierMode::Minimum, &config);
+        assert_outcomes(minimum, &[(0.25, 0.6), (0.75, 1.0)]);
+        // 两种结果都是 1.0，合并为一个
+        let maximum = apply_quantity_modifiers(0.5, &modifiers, QuantityModifierMode::Maximum, &config);
+        assert_outcomes(maximum, &[(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn seed_mixing_is_fixed() {
+        // SplitMix64 以 0 为状态的第一个输出，以及 FNV-1a 的标准测试值
+        assert_eq!(splitmix64(0), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(stable_hash(""), 0xCBF2_9CE4_8422_2325);
+        assert_eq!(stable_hash("a"), 0xAF63_DC4C_8601_EC8C);
+    }
+
+    #[test]
+    fn many_uncertain_modifiers_do_not_blow_up() {
+        let config = AppConfig::for_test();
+        let modifiers: Vec<QuantityModifier> = (0..40).map(|_| modifier(ModificationType::Add, 1.0, Some("RANDOM 0.5"))).collect();
+        let outcomes = apply_quantity_modifiers(0.0, &modifiers, QuantityModifierMode::Stack, &config);
+
+        // 生效个数服从二项分布 B(40, 0.5)
+        assert_eq!(outcomes.len(), 41);
+        assert!((outcomes.iter().map(|(p, _)| p).sum::<f64>() - 1.0).abs() < 1e-9);
+        let mean: f64 = outcomes.iter().map(|(p, v)| p * v).sum();
+        assert!((mean - 20.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,25 @@
+//! tests/common/mod.rs
+//!
+//! 集成测试共用的加载函数：游戏数据与 tests/fixtures 中的配置。
+
+// 每个测试文件只用到其中一部分
+#![allow(dead_code)]
+
+use stardew_fishing_prob::{build_app_config, load_game_data, load_user_config, AppConfig, GameData, UserConfigRaw};
+use std::path::{Path, PathBuf};
+
+pub fn tests_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+pub fn game_data() -> GameData {
+    load_game_data(&Path::new(env!("CARGO_MANIFEST_DIR")).join("data")).expect("game data loads")
+}
+
+pub fn fixture_raw(fixture: &str) -> UserConfigRaw {
+    load_user_config(&tests_dir().join("fixtures").join(format!("{}.json", fixture))).expect("fixture loads")
+}
+
+pub fn fixture_config(fixture: &str, game_data: &GameData) -> AppConfig {
+    build_app_config(&fixture_raw(fixture), game_data).expect("fixture is valid")
+}
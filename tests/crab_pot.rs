@@ -0,0 +1,56 @@
+//! tests/crab_pot.rs
+//!
+//! 蟹笼：水手与诱饵大师职业对每日捕获概率的影响。
+
+mod common;
+
+use common::{fixture_raw, game_data};
+use stardew_fishing_prob::crab_pot::build_crab_pot_report;
+use stardew_fishing_prob::{build_app_config, AppConfig, GameData, UserConfigRaw};
+
+fn setup() -> (UserConfigRaw, GameData) {
+    (fixture_raw("beach_summer_sunny"), game_data())
+}
+
+fn config_with(raw: &UserConfigRaw, game_data: &GameData, bait: &str, professions: &[&str]) -> AppConfig {
+    let mut raw = raw.clone();
+    raw.bait_type = bait.to_string();
+    raw.professions = professions.iter().map(|p| p.to_string()).collect();
+    build_app_config(&raw, game_data).expect("config is valid")
+}
+
+#[test]
+fn luremaster_activates_pots_without_bait() {
+    let (raw, game_data) = setup();
+
+    let without_bait = build_crab_pot_report(&config_with(&raw, &game_data, "None", &[]), &game_data);
+    assert!(without_bait.iter().all(|report| !report.is_active && report.items.is_empty()));
+
+    let luremaster = build_crab_pot_report(&config_with(&raw, &game_data, "None", &["Luremaster"]), &game_data);
+    assert!(luremaster.iter().all(|report| report.is_active && !report.items.is_empty()));
+}
+
+#[test]
+fn mariner_removes_junk_and_picks_uniformly() {
+    let (raw, game_data) = setup();
+    let reports = build_crab_pot_report(&config_with(&raw, &game_data, "鱼饵", &["Mariner"]), &game_data);
+
+    for report in reports {
+        assert_eq!(report.junk_chance, 0.0);
+        let catches: Vec<f64> = report.items.iter().filter(|item| !item.is_junk).map(|item| item.probability).collect();
+        assert!(!catches.is_empty());
+        assert!(catches.iter().all(|p| (p - 1.0 / catches.len() as f64).abs() < 1e-12));
+        assert!(report.items.iter().filter(|item| item.is_junk).all(|item| item.probability == 0.0));
+    }
+}
+
+#[test]
+fn junk_rows_use_object_names() {
+    let (raw, game_data) = setup();
+    let reports = build_crab_pot_report(&config_with(&raw, &game_data, "鱼饵", &[]), &game_data);
+
+    let junk: Vec<&str> = reports[0].items.iter().filter(|item| item.is_junk).map(|item| item.name.as_str()).collect();
+    assert_eq!(junk, ["Trash", "Driftwood", "Broken Glasses", "Broken CD", "Soggy Newspaper"]);
+    let total: f64 = reports[0].items.iter().map(|item| item.probability).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+}
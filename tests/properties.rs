@@ -0,0 +1,73 @@
+//! tests/properties.rs
+//!
+//! 性质测试：用固定种子随机生成配置与成功率，检查与具体数值无关的不变量。
+
+mod common;
+
+use common::{fixture_raw, game_data};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use stardew_fishing_prob::calculator::{group_first_success_probabilities, group_first_success_probabilities_by_permutation};
+use stardew_fishing_prob::{
+    build_app_config, calculate_final_probabilities, calculate_time_segments, filter_items_for_time_segment,
+    get_fish_area_ids, get_resolved_fish_list, ResolvedItem,
+};
+
+#[test]
+fn exact_group_probabilities_match_permutation_average() {
+    let mut rng = StdRng::seed_from_u64(22);
+    for _ in 0..500 {
+        let len = rng.gen_range(0..=8);
+        let rates: Vec<f64> = (0..len)
+            .map(|_| if rng.gen_bool(0.1) { rng.gen_range(0..=1) as f64 } else { rng.gen::<f64>() })
+            .collect();
+
+        let exact = group_first_success_probabilities(&rates);
+        let by_permutation = group_first_success_probabilities_by_permutation(&rates);
+        for (a, b) in exact.iter().zip(&by_permutation) {
+            assert!((a - b).abs() < 1e-12, "rates {:?}: {:?} != {:?}", rates, exact, by_permutation);
+        }
+    }
+}
+
+#[test]
+fn segment_probabilities_sum_to_at_most_one() {
+    let game_data = game_data();
+    let base = fixture_raw("beach_summer_sunny");
+
+    let mut locations: Vec<&String> = game_data.locations.keys().filter(|name| name.as_str() != "Default").collect();
+    locations.sort();
+    let seasons = ["春天", "夏天", "秋天", "冬天"];
+    let weathers = ["晴天", "雨天"];
+    let baits = ["None", "鱼饵", "魔法鱼饵", "野生鱼饵", "豪华鱼饵", "Tuna Bait", "Catfish Bait"];
+
+    let mut rng = StdRng::seed_from_u64(22);
+    for _ in 0..40 {
+        let mut raw = base.clone();
+        raw.location_name = locations.choose(&mut rng).unwrap().to_string();
+        raw.season = seasons.choose(&mut rng).unwrap().to_string();
+        raw.weather = weathers.choose(&mut rng).unwrap().to_string();
+        raw.bait_type = baits.choose(&mut rng).unwrap().to_string();
+        raw.tackles = if rng.gen_bool(0.5) { vec!["珍稀诱钩".to_string()] } else { vec![] };
+        raw.fishing_level = rng.gen_range(0..=10);
+        raw.luck_level = rng.gen_range(0..=3);
+        raw.daily_luck = rng.gen_range(-0.1..=0.125);
+        raw.water_depth = rng.gen_range(0..=4);
+        let config = build_app_config(&raw, &game_data).expect("generated config is valid");
+
+        for area_id in get_fish_area_ids(&config.location_name, &game_data) {
+            let base_items = get_resolved_fish_list(&config, &game_data, &area_id, false);
+            for segment in calculate_time_segments(&base_items, &game_data) {
+                let segment_items = filter_items_for_time_segment(segment, &base_items, &config, &game_data);
+                let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+                let probabilities = calculate_final_probabilities(&segment_items, &config, &game_data);
+
+                let total: f64 = probabilities.iter().map(|p| p.final_prob).sum();
+                let context = format!("{} {:?} {:?} bait {}", config.location_name, area_id, segment, raw.bait_type);
+                assert!(total <= 1.0 + 1e-9, "{}: total {}", context, total);
+                assert!(probabilities.iter().all(|p| (0.0..=1.0 + 1e-12).contains(&p.final_prob)), "{}", context);
+            }
+        }
+    }
+}
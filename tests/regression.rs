@@ -0,0 +1,285 @@
+//! tests/regression.rs
+//!
+//! 回归测试：对 tests/fixtures 中的每个配置，把解析出的物品列表、时间段与各时间段的最终概率
+//! 渲染为文本，与 tests/golden 中的基准表逐字比较。
+//!
+//! 计算逻辑有意改变时，使用 `UPDATE_GOLDEN=1 cargo test --test regression` 重新生成基准表，
+//! 并在提交中检查基准表的差异。
+
+mod common;
+
+use common::{fixture_config, fixture_raw, game_data, tests_dir};
+use stardew_fishing_prob::calculator::resolve_fish_list_traced;
+use stardew_fishing_prob::explain::{explain_fish, ExplainTrace};
+use stardew_fishing_prob::{
+    bite_exclusion, build_app_config, calculate_final_probabilities, calculate_time_segments, filter_items_for_time_segment,
+    get_fish_area_ids, get_resolved_fish_list, resolve_fish_list, segment_exclusion,
+    AppConfig, ExclusionReason, GameData, ResolvedItem, Rod,
+};
+use stardew_fishing_prob::report::{build_location_report, ReportMode, ScenarioReport};
+use stardew_fishing_prob::utils::includes_configured_time;
+use std::fmt::Write;
+use std::fs;
+
+const FIXTURES: &[&str] = &[
+    "beach_summer_sunny",
+    "beach_spring_no_bait_low_level",
+    "beach_winter_magic_bait",
+    "town_river_rainy",
+    "forest_lake_curiosity_lure",
+    "mountain_training_rod",
+    "mountain_specific_bait",
+];
+
+/// 将一个配置下所有 FishArea 的计算结果渲染为基准表。
+/// 概率保留 9 位小数；同一时间段内按 (物品, 来源, 优先级) 排序，避免概率相同的行顺序不稳定。
+fn render(fixture: &str, game_data: &GameData) -> String {
+    let config = fixture_config(fixture, game_data);
+
+    let mut out = String::new();
+    for area_id in get_fish_area_ids(&config.location_name, game_data) {
+        writeln!(out, "== {} ({})", config.location_name, area_id.as_deref().unwrap_or("Default")).unwrap();
+
+        let base_items = get_resolved_fish_list(&config, game_data, &area_id, false);
+        writeln!(out, "resolved:").unwrap();
+        for item in &base_items {
+            writeln!(
+                out,
+                "  {} | {} | {} | prec {} | chance {:.4} | weight {:.4}",
+                item.display_id,
+                item.source,
+                item.source_data.id.as_deref().unwrap_or("-"),
+                item.source_data.precedence,
+                item.source_data.chance,
+                item.condition_weight,
+            ).unwrap();
+        }
+
+        let segments = calculate_time_segments(&base_items, game_data);
+        let segment_list: Vec<String> = segments.iter().map(|(start, end)| format!("{}-{}", start, end)).collect();
+        writeln!(out, "segments: {}", segment_list.join(" ")).unwrap();
+
+        for segment in segments {
+            let segment_items = filter_items_for_time_segment(segment, &base_items, &config, game_data);
+            let segment_items: Vec<&ResolvedItem> = segment_items.iter().collect();
+            let mut probabilities = calculate_final_probabilities(&segment_items, &config, game_data);
+            probabilities.sort_by(|a, b| {
+                (&a.display_id, &a.source_group_id, a.precedence).cmp(&(&b.display_id, &b.source_group_id, b.precedence))
+            });
+
+            writeln!(out, "[{}-{}]", segment.0, segment.1).unwrap();
+            for p in probabilities {
+                writeln!(
+                    out,
+                    "  {} | {} | prec {} | get {:.9} | bite {:.9} | final {:.9}",
+                    p.display_id, p.source_group_id, p.precedence, p.get_chance_prob, p.bite_chance_prob, p.final_prob,
+                ).unwrap();
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn fixtures_match_golden_tables() {
+    let game_data = game_data();
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = Vec::new();
+
+    for &fixture in FIXTURES {
+        let actual = render(fixture, &game_data);
+        let golden_path = tests_dir().join("golden").join(format!("{}.txt", fixture));
+        if update {
+            fs::write(&golden_path, &actual).expect("golden table is writable");
+            continue;
+        }
+
+        let expected = fs::read_to_string(&golden_path)
+            .unwrap_or_else(|_| panic!("缺少基准表 {}，请使用 UPDATE_GOLDEN=1 生成", golden_path.display()));
+        if actual != expected {
+            let first_diff = actual.lines().zip(expected.lines())
+                .find(|(a, e)| a != e)
+                .map(|(a, e)| format!("\n  expected: {}\n  actual:   {}", e, a))
+                .unwrap_or_else(|| "\n  行数不同".to_string());
+            mismatches.push(format!("{}:{}", fixture, first_diff));
+        }
+    }
+
+    assert!(mismatches.is_empty(), "与基准表不一致：\n{}", mismatches.join("\n"));
+}
+
+#[test]
+fn excluded_entries_record_the_failing_check() {
+    let game_data = game_data();
+    let config = fixture_config("mountain_specific_bait", &game_data);
+    let resolution = resolve_fish_list(&config, &game_data, &None, false);
+
+    let legend = resolution.excluded.iter()
+        .find(|entry| entry.source_data.item_id.as_deref() == Some("(O)163"))
+        .expect("Legend is excluded");
+    assert_eq!(legend.reason, ExclusionReason::FishingLevelTooLow { required: 10, current: 8 });
+    assert_eq!(legend.reason.to_string(), "需要钓鱼等级 10，当前为 8");
+
+    // 被过滤的条目不会同时出现在可钓到的物品中
+    assert!(resolution.included.iter().all(|item| {
+        resolution.excluded.iter().all(|entry| entry.source != item.source)
+    }));
+}
+
+#[test]
+fn bite_and_segment_exclusions_record_the_failing_check() {
+    let game_data = game_data();
+    let config = fixture_config("mountain_training_rod", &game_data);
+    let base_items = get_resolved_fish_list(&config, &game_data, &None, false);
+    let bass = base_items.iter().find(|item| item.display_id == "(O)136").expect("Largemouth Bass is resolved");
+
+    assert_eq!(bite_exclusion(bass, &config, &game_data), Some(ExclusionReason::TrainingRodCannotHook { difficulty: 50 }));
+    let iridium_rod = AppConfig { rod: Rod::IridiumRod, ..config.clone() };
+    assert_eq!(bite_exclusion(bass, &iridium_rod, &game_data), None);
+
+    assert_eq!(segment_exclusion((600, 700), bass, &config, &game_data), None);
+    assert_eq!(
+        segment_exclusion((1900, 2600), bass, &config, &game_data),
+        Some(ExclusionReason::OutsideFishTime { windows: vec![(600, 1900)] })
+    );
+}
+
+#[test]
+fn configured_time_selects_segments_instead_of_filtering_entries() {
+    let game_data = game_data();
+    let mut raw = fixture_raw("beach_summer_sunny");
+    raw.season = "冬天".to_string();
+    raw.game_state.passive_festivals.insert("SquidFest".to_string());
+    let is_squid_fest = |item: &ResolvedItem| item.source.to_string() == "Beach#20";
+
+    let untimed = build_app_config(&raw, &game_data).expect("config is valid");
+    raw.game_state.time_of_day = Some(2000);
+    let timed = build_app_config(&raw, &game_data).expect("config is valid");
+
+    // Beach#20 的 Condition 含有 TIME 0600 1800；解析阶段与当前时间无关
+    let base_items = get_resolved_fish_list(&timed, &game_data, &None, false);
+    let sources = |items: &[ResolvedItem]| items.iter().map(|item| item.source.to_string()).collect::<Vec<_>>();
+    assert_eq!(sources(&base_items), sources(&get_resolved_fish_list(&untimed, &game_data, &None, false)));
+    assert!(base_items.iter().any(is_squid_fest));
+
+    // 当前时间只决定分析哪个时间段，条目在该时间段内被 TIME 排除
+    let segments: Vec<(u32, u32)> = calculate_time_segments(&base_items, &game_data).into_iter()
+        .filter(|&segment| includes_configured_time(segment, &timed))
+        .collect();
+    assert_eq!(segments.len(), 1);
+    assert!(!filter_items_for_time_segment(segments[0], &base_items, &timed, &game_data).iter().any(is_squid_fest));
+    let squid_fest = base_items.iter().find(|item| is_squid_fest(item)).unwrap();
+    assert_eq!(
+        segment_exclusion(segments[0], squid_fest, &timed, &game_data),
+        Some(ExclusionReason::ConditionFailed { queries: vec!["TIME 0600 1800".to_string()] })
+    );
+    assert!(filter_items_for_time_segment((1000, 1100), &base_items, &timed, &game_data).iter().any(is_squid_fest));
+}
+
+#[test]
+fn negated_time_conditions_are_decided_per_segment() {
+    let mut game_data = game_data();
+    let config = fixture_config("beach_summer_sunny", &game_data);
+    let index = get_resolved_fish_list(&config, &game_data, &None, false).iter()
+        .find(|item| item.source.location == "Beach" && item.source_data.condition.is_none())
+        .map(|item| item.source.index)
+        .expect("an unconditional Beach entry resolves");
+    game_data.locations.get_mut("Beach").unwrap().fish[index].condition = Some("!TIME 2000".to_string());
+    let is_patched = |item: &ResolvedItem| item.source.location == "Beach" && item.source.index == index;
+
+    // 解析阶段没有具体时间，!TIME 不会排除条目
+    let base_items = get_resolved_fish_list(&config, &game_data, &None, false);
+    assert!(base_items.iter().any(is_patched));
+
+    let segments = calculate_time_segments(&base_items, &game_data);
+    let before = *segments.iter().find(|segment| segment.1 <= 2000).expect("a segment ends by 2000");
+    let after = *segments.iter().find(|segment| segment.0 >= 2000).expect("a segment starts at 2000");
+    assert!(filter_items_for_time_segment(before, &base_items, &config, &game_data).iter().any(is_patched));
+    assert!(!filter_items_for_time_segment(after, &base_items, &config, &game_data).iter().any(is_patched));
+}
+
+#[test]
+fn comparison_scenarios_keep_user_bait_except_on_training_rod() {
+    let game_data = game_data();
+    let mut raw = fixture_raw("beach_summer_sunny");
+    raw.bait_type = "野生鱼饵".to_string();
+    let wild_bait = build_app_config(&raw, &game_data).expect("config is valid");
+    let specific_bait = fixture_config("mountain_specific_bait", &game_data);
+
+    for (config, standard_bait) in [(&wild_bait, Some("(O)774")), (&specific_bait, None)] {
+        let reports = build_location_report(config, &game_data);
+        assert!(!reports.is_empty());
+        for report in reports {
+            assert_eq!(report.mode, ReportMode::Comparison);
+            let (standard, training_rod) = (&report.scenarios[0], &report.scenarios[1]);
+            assert_eq!((standard.name.as_str(), standard.bait_item_id.as_deref()), ("Standard", standard_bait));
+            assert_eq!(standard.bait_target_fish_id, None);
+            assert_eq!((training_rod.name.as_str(), training_rod.bait_item_id.as_deref()), ("TrainingRod", None));
+        }
+    }
+
+    // 训练钓竿场景只有 1 次刷鱼循环，与直接使用不装鱼饵的训练钓竿计算的结果一致
+    raw.rod_type = "训练钓竿".to_string();
+    raw.bait_type = "None".to_string();
+    raw.tackles.clear();
+    let training_rod = build_app_config(&raw, &game_data).expect("config is valid");
+    let expected = build_location_report(&training_rod, &game_data);
+    for (report, expected) in build_location_report(&wild_bait, &game_data).iter().zip(&expected) {
+        let probabilities = |scenario: &ScenarioReport| {
+            scenario.probabilities.iter().map(|p| (p.display_id.clone(), p.final_prob)).collect::<Vec<_>>()
+        };
+        assert_eq!(probabilities(&report.scenarios[1]), probabilities(&expected.scenarios[0]));
+    }
+}
+
+#[test]
+fn bait_scenarios_move_to_a_rod_with_a_bait_slot() {
+    let game_data = game_data();
+    let mut raw = fixture_raw("beach_spring_no_bait_low_level");
+    let bamboo_pole = build_app_config(&raw, &game_data).expect("config is valid");
+    assert_eq!(bamboo_pole.rod.bait_slots(), 0);
+
+    // 竹竿没有鱼饵槽位，特制鱼饵场景改用铱金钓竿，结果与直接用铱金钓竿装该特制鱼饵一致
+    let reports = build_location_report(&bamboo_pole, &game_data);
+    let (report, bait_scenario) = reports.iter()
+        .find_map(|report| report.scenarios.get(2).map(|scenario| (report, scenario)))
+        .expect("some segment has a bait scenario");
+    let fish_name = bait_scenario.name.strip_suffix(" (Iridium Rod)").expect("bait scenario names the rod");
+    assert!(report.scenarios[2..].iter().all(|scenario| scenario.name.ends_with(" (Iridium Rod)")));
+
+    raw.rod_type = "铱金钓竿".to_string();
+    raw.bait_type = format!("{} Bait", fish_name);
+    let iridium_rod = build_app_config(&raw, &game_data).expect("config is valid");
+    let expected = build_location_report(&iridium_rod, &game_data).into_iter()
+        .find(|expected| (&expected.fish_area, expected.start_time) == (&report.fish_area, report.start_time))
+        .expect("same segment exists");
+    let expected = expected.scenarios.iter().find(|scenario| scenario.name == fish_name).expect("same bait scenario exists");
+    assert_eq!(bait_scenario.probabilities.len(), expected.probabilities.len());
+    for (actual, expected) in bait_scenario.probabilities.iter().zip(&expected.probabilities) {
+        assert_eq!(actual.display_id, expected.display_id);
+        assert!((actual.final_prob - expected.final_prob).abs() < 1e-12, "{}", actual.display_id);
+    }
+}
+
+#[test]
+fn legend_trace_matches_untraced_resolution() {
+    let game_data = game_data();
+    let config = fixture_config("mountain_specific_bait", &game_data);
+
+    let explanations = explain_fish("(O)163", &config, &game_data);
+    assert!(explanations.iter()
+        .flat_map(|area| &area.lines)
+        .any(|line| line.text == "[FAIL] MinFishingLevel 10，钓鱼等级 8"));
+
+    // 追踪只记录判断过程，不改变解析结果
+    let summary = |items: &[ResolvedItem]| -> Vec<(String, String, f64)> {
+        items.iter().map(|item| (item.display_id.clone(), item.source.to_string(), item.condition_weight)).collect()
+    };
+    for area_id in get_fish_area_ids(&config.location_name, &game_data) {
+        let mut trace = ExplainTrace::new("(O)163");
+        let traced = resolve_fish_list_traced(&config, &game_data, &area_id, Some(&mut trace));
+        let untraced = get_resolved_fish_list(&config, &game_data, &area_id, false);
+        assert_eq!(summary(&traced), summary(&untraced));
+        assert!(!trace.into_lines().is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+//! tests/timing.rs
+//!
+//! 耗时估算：期望抛竿次数服从几何分布，期望耗时与期望钓获数量由每次抛竿的期望耗时换算。
+
+mod common;
+
+use common::{fixture_config, game_data};
+use stardew_fishing_prob::timing::{build_segment_timing, mean_bite_wait_seconds};
+use stardew_fishing_prob::ProbabilityDetails;
+
+fn probability(display_id: &str, final_prob: f64) -> ProbabilityDetails {
+    ProbabilityDetails {
+        display_id: display_id.to_string(),
+        name: display_id.to_string(),
+        precedence: 0,
+        get_chance_prob: final_prob,
+        bite_chance_prob: 1.0,
+        final_prob,
+        source_group_id: display_id.to_string(),
+        source: "Beach#0".to_string(),
+        catch_success_prob: None,
+        landed_prob: None,
+        expected_count: 1.0,
+        quality: None,
+    }
+}
+
+#[test]
+fn expected_casts_are_geometric() {
+    let game_data = game_data();
+    let config = fixture_config("beach_summer_sunny", &game_data);
+    let model = &config.cast_timing;
+
+    // 河豚 (O)128 需要小游戏，垃圾 (O)168 直接收线，其余 25% 什么都没钓到，同样按收线计时
+    let timing = build_segment_timing(
+        &config,
+        &game_data,
+        None,
+        (1200, 1300),
+        vec![probability("(O)128", 0.25), probability("(O)168", 0.5)],
+    );
+
+    let seconds_per_cast = model.cast_seconds + mean_bite_wait_seconds(&config)
+        + 0.25 * model.minigame_seconds + 0.75 * model.reel_seconds;
+    assert!((timing.seconds_per_cast - seconds_per_cast).abs() < 1e-12);
+    assert!((timing.game_minutes_per_cast - seconds_per_cast / model.seconds_per_game_minute).abs() < 1e-12);
+    // 一小时为 6 个 10 分钟刻
+    let casts_per_segment = 60.0 * model.seconds_per_game_minute / seconds_per_cast;
+    assert!((timing.casts_per_segment - casts_per_segment).abs() < 1e-9);
+
+    let pufferfish = &timing.items[0];
+    assert_eq!(pufferfish.expected_casts, Some(4.0));
+    assert!((pufferfish.expected_game_minutes.unwrap() - 4.0 * timing.game_minutes_per_cast).abs() < 1e-12);
+    assert!((pufferfish.expected_catches - 0.25 * casts_per_segment).abs() < 1e-9);
+    assert_eq!(timing.items[1].expected_casts, Some(2.0));
+
+    let never = build_segment_timing(&config, &game_data, None, (1200, 1300), vec![probability("(O)128", 0.0)]);
+    assert_eq!(never.items[0].expected_casts, None);
+    assert_eq!(never.items[0].expected_game_minutes, None);
+}